@@ -0,0 +1,116 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use game_template_platform::entity::entity_data::EntityData;
+use game_template_platform::entity::entity_type::EntityType;
+use game_template_platform::entity::Entity;
+use game_template_platform::input::InputState;
+use game_template_platform::level::Level;
+use game_template_platform::physics_system::player_physics_system::PlayerPhysicsSystem;
+use game_template_platform::world::World;
+use ggez::graphics::WHITE;
+use ggez::nalgebra::Vector2;
+
+const UNIT_SIZE: f32 = 50.0;
+const GRAVITY: f32 = 0.5;
+
+/// A world filled with rows of static platforms and a single player falling onto them, with the id of the player
+fn create_world(platform_count: usize) -> (World, u32) {
+    let columns = 100;
+    let rows = platform_count.div_ceil(columns);
+    let width = columns as f32 * UNIT_SIZE;
+    let height = (rows * 2 + 2) as f32 * UNIT_SIZE;
+    let platforms = (0..platform_count)
+        .map(|index| {
+            let column = index % columns;
+            let row = index / columns;
+            EntityData::new(
+                column as f32 * UNIT_SIZE + UNIT_SIZE / 2.0,
+                (row * 2 + 2) as f32 * UNIT_SIZE + UNIT_SIZE / 2.0,
+                UNIT_SIZE,
+                UNIT_SIZE,
+                WHITE,
+                EntityType::Platform,
+            )
+        })
+        .collect();
+
+    let mut world = World::new();
+    world
+        .set_gravity(GRAVITY)
+        .set_size(width, height)
+        .set_unit_size(UNIT_SIZE, UNIT_SIZE)
+        .add_level(Level::new(width, height, platforms))
+        .build()
+        .unwrap();
+    let player = world
+        .entity_builder()
+        .create_entity()
+        .location(UNIT_SIZE * 10.0, UNIT_SIZE)
+        .size(UNIT_SIZE, UNIT_SIZE * 2.0)
+        .affected_by_gravity()
+        .physics_system(Box::new(PlayerPhysicsSystem::default()))
+        .build();
+    let player_id = player.id;
+    world.add_entity(player).unwrap();
+    (world, player_id)
+}
+
+fn world_update(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("world_update");
+    let input = InputState::new();
+    for platform_count in [1_000, 5_000, 10_000].iter() {
+        let (mut world, _) = create_world(*platform_count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(platform_count),
+            platform_count,
+            |bencher, _| bencher.iter(|| world.update(&input).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+/// The player moving against the collidables that the grid finds near it, next to the all pairs pass that came
+/// before the grid where every collidable in the world was handed to every moving entity
+fn broad_phase(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("broad_phase");
+    let input = InputState::new();
+    let gravity = Vector2::new(0.0, GRAVITY);
+    for platform_count in [1_000, 5_000, 10_000].iter() {
+        let (mut world, player_id) = create_world(*platform_count);
+        let mut player = world.remove_entity(player_id).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("grid_query", platform_count),
+            platform_count,
+            |bencher, _| {
+                bencher.iter(|| {
+                    let nearby_collidables: Vec<&Entity> = world
+                        .entities_near(player.bounding_box())
+                        .into_iter()
+                        .filter(|entity| entity.collidable)
+                        .collect();
+                    player.update(&gravity, &nearby_collidables, &input)
+                })
+            },
+        );
+
+        let (mut world, player_id) = create_world(*platform_count);
+        let mut player = world.remove_entity(player_id).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("all_pairs", platform_count),
+            platform_count,
+            |bencher, _| {
+                bencher.iter(|| {
+                    let collidables: Vec<&Entity> = world
+                        .entities()
+                        .into_iter()
+                        .filter(|entity| entity.collidable)
+                        .collect();
+                    player.update(&gravity, &collidables, &input)
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, world_update, broad_phase);
+criterion_main!(benches);
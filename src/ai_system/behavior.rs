@@ -0,0 +1,76 @@
+use ggez::nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use super::chase::Chase;
+use super::ledge_walker::LedgeWalker;
+use super::patrol::Patrol;
+use super::sine_flight::SineFlight;
+use super::AiSystem;
+
+/// How an enemy moves, chosen in the level. Speeds are in world units per tick.
+/// ```ron
+/// behavior: Some(Patrol(points: [(100.0, 0.0), (300.0, 0.0)], speed: 1.0)),
+/// behavior: Some(WalkToLedge(speed: 1.5)),
+/// behavior: Some(Chase(range: 300.0, speed: 2.0, flying: true)),
+/// behavior: Some(SineFlight(speed: 1.0, amplitude: 40.0, period_ticks: 120)),
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Behavior {
+    /// Go between the points in order, walking patrols only use the x of each point
+    Patrol {
+        points: Vec<(f32, f32)>,
+        speed: f32,
+        #[serde(default)]
+        flying: bool,
+    },
+    /// Walk until there is a wall or a ledge ahead, then turn around
+    WalkToLedge { speed: f32 },
+    /// Head for the player while they are within range
+    Chase {
+        range: f32,
+        speed: f32,
+        #[serde(default)]
+        flying: bool,
+    },
+    /// Fly sideways while bobbing up and down, turning around at walls
+    SineFlight {
+        speed: f32,
+        amplitude: f32,
+        period_ticks: u32,
+    },
+}
+
+impl Behavior {
+    /// Flying enemies are not pulled down by gravity
+    pub fn is_flying(&self) -> bool {
+        match self {
+            Behavior::Patrol { flying, .. } | Behavior::Chase { flying, .. } => *flying,
+            Behavior::WalkToLedge { .. } => false,
+            Behavior::SineFlight { .. } => true,
+        }
+    }
+
+    pub fn ai_system(&self) -> Box<dyn AiSystem> {
+        match self {
+            Behavior::Patrol {
+                points,
+                speed,
+                flying,
+            } => {
+                let points = points.iter().map(|&(x, y)| Vector2::new(x, y)).collect();
+                Box::new(Patrol::new(points, *speed, *flying))
+            }
+            Behavior::WalkToLedge { speed } => Box::new(LedgeWalker::new(*speed)),
+            Behavior::Chase {
+                range,
+                speed,
+                flying,
+            } => Box::new(Chase::new(*range, *speed, *flying)),
+            Behavior::SineFlight {
+                speed,
+                amplitude,
+                period_ticks,
+            } => Box::new(SineFlight::new(*speed, *amplitude, *period_ticks)),
+        }
+    }
+}
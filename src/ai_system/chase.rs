@@ -0,0 +1,65 @@
+use ggez::nalgebra::Vector2;
+
+use super::{towards, AiSystem, Senses};
+
+/// Heads for the player while they are within range and waits where it is otherwise. Walking chasers only
+/// follow the player sideways.
+#[derive(Debug)]
+pub struct Chase {
+    range: f32,
+    speed: f32,
+    flying: bool,
+}
+
+impl Chase {
+    pub fn new(range: f32, speed: f32, flying: bool) -> Self {
+        Self {
+            range,
+            speed,
+            flying,
+        }
+    }
+}
+
+impl AiSystem for Chase {
+    fn think(&mut self, senses: &Senses) -> Vector2<f32> {
+        match senses.player_location {
+            Some(player) if (player - senses.location).norm() <= self.range => {
+                let target = if self.flying {
+                    player
+                } else {
+                    Vector2::new(player.x, senses.location.y)
+                };
+                towards(senses.location, target, self.speed)
+            }
+            _ => Vector2::new(0.0, 0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::physics_system::contacts::Contacts;
+
+    use super::*;
+
+    #[test]
+    fn ci_test_chase_only_within_range() {
+        let mut senses = Senses {
+            location: Vector2::new(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            contacts: Contacts::default(),
+            player_location: Some(Vector2::new(100.0, 0.0)),
+            nearby: &[],
+        };
+        let mut chase = Chase::new(50.0, 2.0, false);
+        assert_eq!(chase.think(&senses), Vector2::new(0.0, 0.0));
+
+        senses.player_location = Some(Vector2::new(-30.0, -20.0));
+        assert_eq!(chase.think(&senses), Vector2::new(-2.0, 0.0));
+
+        senses.player_location = None;
+        assert_eq!(chase.think(&senses), Vector2::new(0.0, 0.0));
+    }
+}
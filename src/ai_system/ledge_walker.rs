@@ -0,0 +1,80 @@
+use ggez::nalgebra::Vector2;
+
+use super::{AiSystem, Senses};
+
+/// Walks in one direction until it reaches a wall or the edge of the ground, then turns around
+#[derive(Debug)]
+pub struct LedgeWalker {
+    speed: f32,
+    /// -1.0 when walking left, 1.0 when walking right
+    direction: f32,
+}
+
+impl LedgeWalker {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            direction: 1.0,
+        }
+    }
+
+    /// Is there ground just past the front foot
+    fn ground_ahead(&self, senses: &Senses) -> bool {
+        let ahead = Vector2::new(
+            senses.location.x + self.direction * (senses.width / 2.0 + 1.0),
+            senses.location.y + senses.height / 2.0 + 1.0,
+        );
+        senses.is_solid_at(ahead)
+    }
+}
+
+impl AiSystem for LedgeWalker {
+    fn think(&mut self, senses: &Senses) -> Vector2<f32> {
+        // only look for ledges on the ground, otherwise we would turn around in the air forever
+        if senses.is_blocked(self.direction)
+            || (senses.contacts.bottom && !self.ground_ahead(senses))
+        {
+            self.direction = -self.direction;
+        }
+        Vector2::new(self.direction * self.speed, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::builder::EntityBuilder;
+    use crate::physics_system::contacts::Contacts;
+
+    use super::*;
+
+    #[test]
+    fn ci_test_ledge_walker_turns_at_ledges_and_walls() {
+        let floor = EntityBuilder::new()
+            .create_entity()
+            .location(0.0, 10.0)
+            .size(20.0, 10.0)
+            .collidable()
+            .build();
+        let nearby = [&floor];
+        let mut senses = Senses {
+            location: Vector2::new(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            contacts: Contacts {
+                bottom: true,
+                ..Contacts::default()
+            },
+            player_location: None,
+            nearby: &nearby,
+        };
+        let mut walker = LedgeWalker::new(2.0);
+        assert_eq!(walker.think(&senses), Vector2::new(2.0, 0.0));
+
+        senses.location.x = 6.0;
+        assert_eq!(walker.think(&senses), Vector2::new(-2.0, 0.0));
+
+        senses.location.x = 0.0;
+        senses.contacts.left = true;
+        assert_eq!(walker.think(&senses), Vector2::new(2.0, 0.0));
+    }
+}
@@ -0,0 +1,102 @@
+pub mod behavior;
+pub mod chase;
+pub mod ledge_walker;
+pub mod patrol;
+pub mod sine_flight;
+
+use std::fmt::Debug;
+
+use ggez::nalgebra::Vector2;
+
+use crate::entity::Entity;
+use crate::physics_system::contacts::Contacts;
+
+/// Decides where an entity that is not controlled by the player wants to go
+pub trait AiSystem
+where
+    Self: Debug,
+{
+    /// Called once per fixed update before the physics, returns how far to move this tick
+    fn think(&mut self, senses: &Senses) -> Vector2<f32>;
+}
+
+/// What an entity knows about itself and the world around it when deciding where to go
+#[derive(Debug)]
+pub struct Senses<'a> {
+    pub location: Vector2<f32>,
+    pub width: f32,
+    pub height: f32,
+    /// the sides that were blocked during the last update
+    pub contacts: Contacts,
+    /// where the player was at the start of the tick
+    pub player_location: Option<Vector2<f32>>,
+    /// the collidable entities near this one
+    pub nearby: &'a [&'a Entity],
+}
+
+impl Senses<'_> {
+    /// Is there something collidable at this point, such as the ground ahead
+    pub fn is_solid_at(&self, point: Vector2<f32>) -> bool {
+        self.nearby.iter().any(|other| {
+            let bounding_box = other.bounding_box();
+            other.collidable && bounding_box.contains([point.x, point.y])
+        })
+    }
+
+    /// Something is in the way on the side we are moving towards
+    pub fn is_blocked(&self, direction: f32) -> bool {
+        (direction > 0.0 && self.contacts.right) || (direction < 0.0 && self.contacts.left)
+    }
+}
+
+/// Move straight towards the target without going past it
+fn towards(from: Vector2<f32>, to: Vector2<f32>, speed: f32) -> Vector2<f32> {
+    let difference = to - from;
+    let distance = difference.norm();
+    if distance <= speed {
+        difference
+    } else {
+        difference / distance * speed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::builder::EntityBuilder;
+
+    use super::*;
+
+    #[test]
+    fn ci_test_senses_find_solid_ground() {
+        let floor = EntityBuilder::new()
+            .create_entity()
+            .location(0.0, 10.0)
+            .size(20.0, 10.0)
+            .collidable()
+            .build();
+        let senses = Senses {
+            location: Vector2::new(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            contacts: Contacts::default(),
+            player_location: None,
+            nearby: &[&floor],
+        };
+        assert!(senses.is_solid_at(Vector2::new(5.0, 6.0)));
+        assert!(!senses.is_solid_at(Vector2::new(11.0, 6.0)));
+        assert!(!senses.is_blocked(1.0));
+    }
+
+    #[test]
+    fn ci_test_towards_does_not_overshoot() {
+        let from = Vector2::new(0.0, 0.0);
+        assert_eq!(
+            towards(from, Vector2::new(10.0, 0.0), 2.0),
+            Vector2::new(2.0, 0.0)
+        );
+        assert_eq!(
+            towards(from, Vector2::new(1.0, 0.0), 2.0),
+            Vector2::new(1.0, 0.0)
+        );
+    }
+}
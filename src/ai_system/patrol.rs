@@ -0,0 +1,76 @@
+use ggez::nalgebra::Vector2;
+
+use super::{towards, AiSystem, Senses};
+
+/// Goes from one point to the next and back to the first after the last. Walking patrols only move
+/// sideways so they go to the next point when they are level with this one or a wall is in the way.
+#[derive(Debug)]
+pub struct Patrol {
+    points: Vec<Vector2<f32>>,
+    speed: f32,
+    flying: bool,
+    /// the point we are heading to
+    next: usize,
+}
+
+impl Patrol {
+    pub fn new(points: Vec<Vector2<f32>>, speed: f32, flying: bool) -> Self {
+        Self {
+            points,
+            speed,
+            flying,
+            next: 0,
+        }
+    }
+}
+
+impl AiSystem for Patrol {
+    fn think(&mut self, senses: &Senses) -> Vector2<f32> {
+        let target = match self.points.get(self.next) {
+            Some(point) if self.flying => *point,
+            Some(point) => Vector2::new(point.x, senses.location.y),
+            None => return Vector2::new(0.0, 0.0),
+        };
+        let velocity = towards(senses.location, target, self.speed);
+        let blocked = !self.flying && senses.is_blocked(velocity.x);
+        if velocity.norm() < self.speed || blocked {
+            self.next = (self.next + 1) % self.points.len();
+        }
+        velocity
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::physics_system::contacts::Contacts;
+
+    use super::*;
+
+    #[test]
+    fn ci_test_patrol_goes_back_and_forth() {
+        let mut senses = Senses {
+            location: Vector2::new(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            contacts: Contacts::default(),
+            player_location: None,
+            nearby: &[],
+        };
+        let mut patrol = Patrol::new(
+            vec![Vector2::new(3.0, 50.0), Vector2::new(0.0, 50.0)],
+            2.0,
+            false,
+        );
+        let mut visited = vec![];
+        for _ in 0..6 {
+            senses.location += patrol.think(&senses);
+            visited.push(senses.location.x);
+        }
+        assert_eq!(visited, vec![2.0, 3.0, 1.0, 0.0, 2.0, 3.0]);
+        assert_eq!(senses.location.y, 0.0);
+
+        let mut flying_patrol = Patrol::new(vec![Vector2::new(0.0, 4.0)], 2.0, true);
+        senses.location = Vector2::new(0.0, 0.0);
+        assert_eq!(flying_patrol.think(&senses), Vector2::new(0.0, 2.0));
+    }
+}
@@ -0,0 +1,74 @@
+use std::f32::consts::TAU;
+
+use ggez::nalgebra::Vector2;
+
+use super::{AiSystem, Senses};
+
+/// Flies sideways while bobbing up and down around the height it started at, turning around at walls
+#[derive(Debug)]
+pub struct SineFlight {
+    speed: f32,
+    amplitude: f32,
+    period_ticks: u32,
+    tick: u32,
+    /// -1.0 when flying left, 1.0 when flying right
+    direction: f32,
+}
+
+impl SineFlight {
+    /// The amplitude is how far above and below the start it goes and the period is how many ticks one bob takes
+    pub fn new(speed: f32, amplitude: f32, period_ticks: u32) -> Self {
+        Self {
+            speed,
+            amplitude,
+            period_ticks: period_ticks.max(1),
+            tick: 0,
+            direction: 1.0,
+        }
+    }
+}
+
+impl AiSystem for SineFlight {
+    fn think(&mut self, senses: &Senses) -> Vector2<f32> {
+        if senses.is_blocked(self.direction) {
+            self.direction = -self.direction;
+        }
+        // moving by the change in the sine each tick keeps the height exactly on the wave
+        let period = self.period_ticks as f32;
+        let height = |tick: u32| self.amplitude * (TAU * tick as f32 / period).sin();
+        let vertical = height(self.tick + 1) - height(self.tick);
+        self.tick = (self.tick + 1) % self.period_ticks;
+        Vector2::new(self.direction * self.speed, vertical)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::physics_system::contacts::Contacts;
+
+    use super::*;
+
+    #[test]
+    fn ci_test_sine_flight_bobs_around_the_start() {
+        let mut senses = Senses {
+            location: Vector2::new(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            contacts: Contacts::default(),
+            player_location: None,
+            nearby: &[],
+        };
+        let mut flight = SineFlight::new(1.0, 10.0, 40);
+        let mut highest: f32 = 0.0;
+        for _ in 0..40 {
+            senses.location += flight.think(&senses);
+            highest = highest.max(senses.location.y.abs());
+        }
+        assert!((highest - 10.0).abs() < 0.001);
+        assert!(senses.location.y.abs() < 0.001);
+        assert!((senses.location.x - 40.0).abs() < 0.001);
+
+        senses.contacts.right = true;
+        assert_eq!(flight.think(&senses).x, -1.0);
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlaybackMode {
+    /// Start over from the first frame after the last one
+    Loop,
+    /// Stay on the last frame
+    Once,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    /// the key of the sprite in the loaded sprite atlases
+    pub sprite: String,
+    /// how many update ticks the frame is shown for
+    pub ticks: u32,
+    /// sent when the frame starts, such as a footstep when a foot hits the ground
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationClip {
+    pub mode: PlaybackMode,
+    pub frames: Vec<Frame>,
+}
@@ -0,0 +1,308 @@
+pub mod clip;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use eyre::{bail, eyre, Result, WrapErr};
+use ggez::nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use crate::entity::entity_state::EntityState;
+
+use self::clip::{AnimationClip, PlaybackMode};
+
+/// The clips of an entity and which one plays in each state, written in RON, for example
+/// ```ron
+/// (
+///     clips: {
+///         "idle": (mode: Loop, frames: [(sprite: "player_idle_0", ticks: 25), (sprite: "player_idle_1", ticks: 25)]),
+///         "run": (mode: Loop, frames: [(sprite: "player_run_0", ticks: 6, events: ["footstep"])]),
+///     },
+///     states: [(Standing, "idle")],
+///     running: Some((clip: "run", min_speed: 0.5)),
+/// )
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationSet {
+    pub clips: BTreeMap<String, AnimationClip>,
+    pub states: Vec<(EntityState, String)>,
+    /// played instead of the standing clip while moving sideways faster than the min speed
+    #[serde(default)]
+    pub running: Option<RunningClip>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunningClip {
+    pub clip: String,
+    pub min_speed: f32,
+}
+
+impl AnimationSet {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .wrap_err("could not read the file")
+            .and_then(|contents| Self::from_ron_str(&contents))
+            .wrap_err_with(|| format!("could not load animations {}", path.display()))
+    }
+
+    pub fn from_ron_str(ron: &str) -> Result<Self> {
+        let animation_set: Self = ron::de::from_str(ron).map_err(|error| {
+            eyre!(
+                "error in animations at line {}, column {}: {}",
+                error.position.line,
+                error.position.col,
+                error.code
+            )
+        })?;
+        animation_set.validate()?;
+        Ok(animation_set)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, clip) in self.clips.iter() {
+            if clip.frames.is_empty() {
+                bail!("animation clip {} has no frames", name);
+            }
+            if clip.frames.iter().any(|frame| frame.ticks == 0) {
+                bail!("animation clip {} has a frame that lasts zero ticks", name);
+            }
+        }
+        let state_clips = self.states.iter().map(|(_, clip)| clip);
+        let running_clip = self.running.iter().map(|running| &running.clip);
+        if let Some(missing) = state_clips
+            .chain(running_clip)
+            .find(|clip| !self.clips.contains_key(*clip))
+        {
+            bail!("there is no animation clip named {}", missing);
+        }
+        Ok(())
+    }
+}
+
+/// Plays the clips of an animation set one update tick at a time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animator {
+    animation_set: AnimationSet,
+    clip_name: Option<String>,
+    frame_index: usize,
+    ticks_in_frame: u32,
+    /// events from frames that started since the last time they were taken
+    events: Vec<String>,
+}
+
+impl Animator {
+    pub fn new(animation_set: AnimationSet) -> Self {
+        Self {
+            animation_set,
+            clip_name: None,
+            frame_index: 0,
+            ticks_in_frame: 0,
+            events: vec![],
+        }
+    }
+
+    /// Start playing the clip from the beginning, unless it is already playing
+    pub fn play(&mut self, name: &str) -> Result<()> {
+        if self.clip_name.as_deref() == Some(name) {
+            return Ok(());
+        }
+        if !self.animation_set.clips.contains_key(name) {
+            bail!("there is no animation clip named {}", name);
+        }
+        self.clip_name = Some(name.to_owned());
+        self.frame_index = 0;
+        self.ticks_in_frame = 0;
+        self.start_frame();
+        Ok(())
+    }
+
+    /// Pick the clip for what the entity is doing and move the animation forward by one tick
+    pub fn update(&mut self, state: EntityState, velocity: &Vector2<f32>) {
+        if let Some(name) = self.clip_for(state, velocity) {
+            // the clip names were checked when the animation set was loaded
+            let _ = self.play(&name);
+        }
+        self.advance();
+    }
+
+    pub fn clip_name(&self) -> Option<&str> {
+        self.clip_name.as_deref()
+    }
+
+    /// The sprite of the frame being shown
+    pub fn current_sprite(&self) -> Option<&str> {
+        self.current_clip()
+            .and_then(|clip| clip.frames.get(self.frame_index))
+            .map(|frame| frame.sprite.as_str())
+    }
+
+    /// True when a clip that plays once has reached the end of its last frame
+    pub fn is_finished(&self) -> bool {
+        match self.current_clip() {
+            Some(clip) => {
+                clip.mode == PlaybackMode::Once
+                    && self.frame_index == clip.frames.len() - 1
+                    && self.ticks_in_frame >= clip.frames[self.frame_index].ticks
+            }
+            None => false,
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn clip_for(&self, state: EntityState, velocity: &Vector2<f32>) -> Option<String> {
+        if let Some(running) = &self.animation_set.running {
+            if state == EntityState::Standing && velocity.x.abs() >= running.min_speed {
+                return Some(running.clip.clone());
+            }
+        }
+        self.animation_set
+            .states
+            .iter()
+            .find(|(clip_state, _)| *clip_state == state)
+            .map(|(_, clip)| clip.clone())
+    }
+
+    fn current_clip(&self) -> Option<&AnimationClip> {
+        self.animation_set.clips.get(self.clip_name.as_ref()?)
+    }
+
+    fn advance(&mut self) {
+        let (frame_ticks, frame_count, mode) = match self.current_clip() {
+            Some(clip) => (
+                clip.frames[self.frame_index].ticks,
+                clip.frames.len(),
+                clip.mode,
+            ),
+            None => return,
+        };
+        self.ticks_in_frame += 1;
+        if self.ticks_in_frame < frame_ticks {
+            return;
+        }
+
+        let next_frame = self.frame_index + 1;
+        if next_frame < frame_count {
+            self.frame_index = next_frame;
+        } else if mode == PlaybackMode::Loop {
+            self.frame_index = 0;
+        } else {
+            // stay on the last frame without sending its events again
+            self.ticks_in_frame = frame_ticks;
+            return;
+        }
+        self.ticks_in_frame = 0;
+        self.start_frame();
+    }
+
+    fn start_frame(&mut self) {
+        let events = self
+            .current_clip()
+            .and_then(|clip| clip.frames.get(self.frame_index))
+            .map(|frame| frame.events.clone())
+            .unwrap_or_default();
+        self.events.extend(events);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ANIMATIONS: &str = r#"(
+        clips: {
+            "idle": (mode: Loop, frames: [(sprite: "idle_0", ticks: 2), (sprite: "idle_1", ticks: 1)]),
+            "run": (mode: Loop, frames: [
+                (sprite: "run_0", ticks: 1),
+                (sprite: "run_1", ticks: 1, events: ["footstep"]),
+            ]),
+            "jump": (mode: Once, frames: [(sprite: "jump_0", ticks: 1), (sprite: "jump_1", ticks: 2)]),
+        },
+        states: [(Standing, "idle"), (Jumping, "jump")],
+        running: Some((clip: "run", min_speed: 0.5)),
+    )"#;
+
+    fn animator() -> Animator {
+        Animator::new(AnimationSet::from_ron_str(ANIMATIONS).unwrap())
+    }
+
+    fn still() -> Vector2<f32> {
+        Vector2::new(0.0, 0.0)
+    }
+
+    #[test]
+    fn ci_test_looping_clip() {
+        let mut animator = animator();
+        animator.play("idle").unwrap();
+        let mut sprites = vec![animator.current_sprite().unwrap().to_owned()];
+        for _ in 0..4 {
+            animator.update(EntityState::Standing, &still());
+            sprites.push(animator.current_sprite().unwrap().to_owned());
+        }
+        assert_eq!(
+            sprites,
+            vec!["idle_0", "idle_0", "idle_1", "idle_0", "idle_0"]
+        );
+        assert!(!animator.is_finished());
+    }
+
+    #[test]
+    fn ci_test_one_shot_clip_stays_on_the_last_frame() {
+        let mut animator = animator();
+        animator.update(EntityState::Jumping, &still());
+        assert_eq!(animator.current_sprite(), Some("jump_1"));
+        assert!(!animator.is_finished());
+        animator.update(EntityState::Jumping, &still());
+        animator.update(EntityState::Jumping, &still());
+        animator.update(EntityState::Jumping, &still());
+        assert_eq!(animator.current_sprite(), Some("jump_1"));
+        assert!(animator.is_finished());
+    }
+
+    #[test]
+    fn ci_test_state_picks_the_clip() {
+        let mut animator = animator();
+        animator.update(EntityState::Standing, &Vector2::new(1.0, 0.0));
+        assert_eq!(animator.clip_name(), Some("run"));
+        animator.update(EntityState::Standing, &still());
+        assert_eq!(animator.clip_name(), Some("idle"));
+        animator.update(EntityState::Jumping, &Vector2::new(1.0, 0.0));
+        assert_eq!(animator.clip_name(), Some("jump"));
+        // there is no clip for falling so the last one keeps playing
+        animator.update(EntityState::Falling, &still());
+        assert_eq!(animator.clip_name(), Some("jump"));
+    }
+
+    #[test]
+    fn ci_test_frames_send_their_events() {
+        let mut animator = animator();
+        let running = Vector2::new(2.0, 0.0);
+        animator.update(EntityState::Standing, &running);
+        assert_eq!(animator.take_events(), vec!["footstep"]);
+        animator.update(EntityState::Standing, &running);
+        assert!(animator.take_events().is_empty());
+        animator.update(EntityState::Standing, &running);
+        assert_eq!(animator.take_events(), vec!["footstep"]);
+    }
+
+    #[test]
+    fn ci_test_unknown_clips_are_errors() {
+        let error = AnimationSet::from_ron_str(
+            &ANIMATIONS.replace(r#"(Jumping, "jump")"#, r#"(Jumping, "leap")"#),
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "there is no animation clip named leap");
+        assert!(animator().play("leap").is_err());
+    }
+
+    #[test]
+    fn ci_test_player_animations_in_resources_are_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/animations/player.ron");
+        let animation_set = AnimationSet::from_path(path).unwrap();
+        assert!(animation_set.clips.contains_key("idle"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Play the game without a window and print where everything that moves ends up, for testing gameplay on machines
+//! without a GPU.
+//!
+//! ```text
+//! simulate [--level PATH]... [--ticks N] [--input SCRIPT] [--every N] [--record REPLAY]
+//! simulate --replay REPLAY [--every N]
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+
+use eyre::{bail, eyre, Result};
+use game_template_platform::config::Config;
+use game_template_platform::input::input_script::InputScript;
+use game_template_platform::input::InputState;
+use game_template_platform::replay::replay_player::ReplayPlayer;
+use game_template_platform::replay::Replay;
+use game_template_platform::world::World;
+
+const USAGE: &str =
+    "usage: simulate [--level PATH]... [--ticks N] [--input SCRIPT] [--every N] [--record REPLAY]
+       simulate --replay REPLAY [--every N]";
+
+struct Options {
+    level_paths: Vec<PathBuf>,
+    ticks: u32,
+    input_script: InputScript,
+    /// print the entities every this many ticks as well as at the end
+    every: Option<u32>,
+    record_path: Option<PathBuf>,
+    /// play this replay instead of the levels and the input script
+    replay: Option<Replay>,
+}
+
+fn parse_options() -> Result<Options> {
+    let mut options = Options {
+        level_paths: vec![],
+        ticks: 100,
+        input_script: InputScript::default(),
+        every: None,
+        record_path: None,
+        replay: None,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| eyre!("{} needs a value\n{}", arg, USAGE))
+        };
+        match arg.as_str() {
+            "--level" => options.level_paths.push(PathBuf::from(value()?)),
+            "--ticks" => options.ticks = value()?.parse()?,
+            "--input" => options.input_script = InputScript::from_path(value()?)?,
+            "--every" => options.every = Some(value()?.parse()?),
+            "--record" => options.record_path = Some(PathBuf::from(value()?)),
+            "--replay" => options.replay = Some(Replay::from_path(value()?)?),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => bail!("unknown argument {}\n{}", arg, USAGE),
+        }
+    }
+    Ok(options)
+}
+
+fn print_entities(tick: u32, world: &World) {
+    // platforms never move, so only print the entities that can
+    for entity in world
+        .entities()
+        .into_iter()
+        .filter(|entity| entity.physics_system.is_some())
+    {
+        let entity_type = match entity.entity_type {
+            Some(entity_type) => format!("{:?}", entity_type),
+            None => "Unknown".to_owned(),
+        };
+        println!(
+            "tick {} entity {} {} x {:.2} y {:.2} state {:?}",
+            tick,
+            entity.id,
+            entity_type,
+            entity.location.x,
+            entity.location.y,
+            entity.get_state()
+        );
+    }
+}
+
+/// Update the world once and print what happened
+fn step(world: &mut World, tick: u32, input: &InputState, options: &Options) -> Result<()> {
+    world.update(input)?;
+    let tick = tick + 1;
+    for event in world.drain_events() {
+        println!("tick {} event {:?}", tick, event);
+    }
+    if tick == options.ticks
+        || options
+            .every
+            .is_some_and(|every| every > 0 && tick.is_multiple_of(every))
+    {
+        print_entities(tick, world);
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let mut options = parse_options()?;
+    let (mut config, _config_path) = Config::load_from_environment()?;
+    let camera_size = (config.virtual_width, config.virtual_height);
+
+    if let Some(replay) = options.replay.take() {
+        options.ticks = replay.ticks() as u32;
+        let mut replay_player = ReplayPlayer::new(replay);
+        let mut world = replay_player.create_world(&config, camera_size)?;
+        print_entities(0, &world);
+        let mut tick = 0;
+        while let Some(input) = replay_player.next_input() {
+            step(&mut world, tick, input, &options)?;
+            tick += 1;
+        }
+        return Ok(());
+    }
+
+    if !options.level_paths.is_empty() {
+        config.level_paths = options.level_paths.clone();
+    }
+    let mut world = World::from_config(&config, camera_size)?;
+    let mut recording = Replay::new(world.levels().to_vec());
+    let mut input = InputState::new();
+    print_entities(0, &world);
+    for tick in 0..options.ticks {
+        options.input_script.apply(tick, &mut input);
+        recording.record(&input);
+        step(&mut world, tick, &input, &options)?;
+        input.end_tick();
+    }
+    if let Some(path) = &options.record_path {
+        recording.save(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,433 @@
+pub mod shake;
+pub mod zoom;
+
+use ggez::graphics::{DrawParam, Rect};
+use ggez::nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use self::shake::Shake;
+use self::zoom::Zoom;
+
+/// How the camera catches up with where it wants to be
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum CameraSmoothing {
+    /// Jump straight to the target
+    #[default]
+    None,
+    /// Move this part of the remaining distance each tick, between 0.0 and 1.0
+    Lerp(f32),
+    /// A critically damped spring, which eases in and out without overshooting. Higher frequencies are stiffer,
+    /// values above 1.0 are not stable at one step per tick.
+    Spring { frequency: f32 },
+}
+
+/// What the camera is following during an update
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CameraTarget {
+    pub location: Vector2<f32>,
+    pub velocity: Vector2<f32>,
+}
+
+/// The part of the world that is shown on screen, x and y are the center of the view
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Camera {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// where the camera was before the last update, used to smooth out drawing between updates
+    previous: Vector2<f32>,
+    velocity: Vector2<f32>,
+    target_id: Option<u32>,
+    /// the size of the box around the center of the view that the target can move in without moving the camera
+    dead_zone: (f32, f32),
+    smoothing: CameraSmoothing,
+    /// how many ticks of the target's velocity to look ahead by
+    look_ahead_ticks: f32,
+    max_look_ahead: (f32, f32),
+    shake: Shake,
+    zoom: Zoom,
+}
+
+impl Camera {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            previous: Vector2::new(x, y),
+            velocity: Vector2::new(0.0, 0.0),
+            target_id: None,
+            dead_zone: (0.0, 0.0),
+            smoothing: CameraSmoothing::None,
+            look_ahead_ticks: 0.0,
+            max_look_ahead: (0.0, 0.0),
+            shake: Shake::default(),
+            zoom: Zoom::default(),
+        }
+    }
+
+    /// The part of the world that the camera shows, which is smaller than the screen when zoomed in
+    pub fn as_rect(&self) -> Rect {
+        let (width, height) = self.view_size();
+        Rect::new(self.x - width / 2.0, self.y - height / 2.0, width, height)
+    }
+
+    /// Change how much of the world is shown, such as when the window is resized
+    pub fn set_size(&mut self, width: f32, height: f32) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Turn a location on the screen, in the virtual resolution, into a location in the world
+    pub fn screen_to_world(&self, point: Vector2<f32>) -> Vector2<f32> {
+        let center = Vector2::new(self.width / 2.0, self.height / 2.0);
+        Vector2::new(self.x, self.y) + (point - center) / self.zoom()
+    }
+
+    /// Keep the entity with this id on screen
+    pub fn follow(&mut self, id: u32) -> &mut Self {
+        self.target_id = Some(id);
+        self
+    }
+
+    pub fn stop_following(&mut self) -> &mut Self {
+        self.target_id = None;
+        self
+    }
+
+    pub fn target_id(&self) -> Option<u32> {
+        self.target_id
+    }
+
+    pub fn set_dead_zone(&mut self, width: f32, height: f32) -> &mut Self {
+        self.dead_zone = (width, height);
+        self
+    }
+
+    pub fn set_smoothing(&mut self, smoothing: CameraSmoothing) -> &mut Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    /// Show more of the level in the direction that the target is moving
+    pub fn set_look_ahead(&mut self, ticks: f32, max_x: f32, max_y: f32) -> &mut Self {
+        self.look_ahead_ticks = ticks;
+        self.max_look_ahead = (max_x, max_y);
+        self
+    }
+
+    pub fn set_shake(&mut self, shake: Shake) -> &mut Self {
+        self.shake = shake;
+        self
+    }
+
+    /// Shake the screen, 1.0 is the most shaking there can be
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.shake.add_trauma(amount);
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom.current()
+    }
+
+    /// Ease to the new zoom over the ticks, 2.0 shows everything twice as big
+    pub fn zoom_to(&mut self, zoom: f32, ticks: u32) {
+        self.zoom.zoom_to(zoom, ticks);
+    }
+
+    /// Move towards the target by one tick, without showing anything outside of the level
+    pub fn update(&mut self, target: Option<CameraTarget>, level_size: (f32, f32)) {
+        self.shake.update();
+        self.zoom.update();
+        self.previous = Vector2::new(self.x, self.y);
+        let current = self.previous;
+        let wanted = match target {
+            Some(target) => {
+                self.wanted_location(target.location + self.look_ahead(target.velocity))
+            }
+            None => current,
+        };
+
+        let next = match self.smoothing {
+            CameraSmoothing::None => wanted,
+            CameraSmoothing::Lerp(amount) => current + (wanted - current) * amount,
+            CameraSmoothing::Spring { frequency } => {
+                let acceleration =
+                    (wanted - current) * frequency * frequency - self.velocity * 2.0 * frequency;
+                self.velocity += acceleration;
+                current + self.velocity
+            }
+        };
+        self.set_center(self.clamp_to_level(next, level_size));
+    }
+
+    /// Go straight to the target, such as when a level starts, so that the camera does not sweep across the level
+    pub fn snap_to(&mut self, target: Vector2<f32>, level_size: (f32, f32)) {
+        let location = self.clamp_to_level(target, level_size);
+        self.set_center(location);
+        self.previous = location;
+        self.velocity *= 0.0;
+    }
+
+    /// Where the camera is drawn from, between where it was on the last update and where it is now by lag. This
+    /// is the same way that entities are drawn so that they stay still relative to the camera.
+    pub fn drawn_location(&self, lag: f32) -> Vector2<f32> {
+        let current = Vector2::new(self.x, self.y);
+        self.previous + (current - self.previous) * lag
+    }
+
+    /// The part of the world that is on screen when drawing with lag
+    pub fn view(&self, lag: f32) -> Rect {
+        let location = self.drawn_location(lag);
+        let (width, height) = self.view_size();
+        Rect::new(
+            location.x - width / 2.0,
+            location.y - height / 2.0,
+            width,
+            height,
+        )
+    }
+
+    /// The transform that moves and scales the world so that the camera is in the middle of the screen
+    pub fn transform(&self, lag: f32) -> DrawParam {
+        let location = self.drawn_location(lag) + self.shake.offset();
+        let zoom = self.zoom();
+        DrawParam::new()
+            .dest([
+                self.width / 2.0 - location.x * zoom,
+                self.height / 2.0 - location.y * zoom,
+            ])
+            .scale([zoom, zoom])
+    }
+
+    fn view_size(&self) -> (f32, f32) {
+        let zoom = self.zoom();
+        (self.width / zoom, self.height / zoom)
+    }
+
+    fn look_ahead(&self, velocity: Vector2<f32>) -> Vector2<f32> {
+        let look_ahead = velocity * self.look_ahead_ticks;
+        Vector2::new(
+            look_ahead
+                .x
+                .clamp(-self.max_look_ahead.0, self.max_look_ahead.0),
+            look_ahead
+                .y
+                .clamp(-self.max_look_ahead.1, self.max_look_ahead.1),
+        )
+    }
+
+    fn wanted_location(&self, target: Vector2<f32>) -> Vector2<f32> {
+        let half_dead_zone = Vector2::new(self.dead_zone.0 / 2.0, self.dead_zone.1 / 2.0);
+        let current = Vector2::new(self.x, self.y);
+        let minimum = target - half_dead_zone;
+        let maximum = target + half_dead_zone;
+        Vector2::new(
+            current.x.max(minimum.x).min(maximum.x),
+            current.y.max(minimum.y).min(maximum.y),
+        )
+    }
+
+    fn clamp_to_level(
+        &self,
+        location: Vector2<f32>,
+        (level_width, level_height): (f32, f32),
+    ) -> Vector2<f32> {
+        let (width, height) = self.view_size();
+        Vector2::new(
+            clamp_axis(location.x, width, level_width),
+            clamp_axis(location.y, height, level_height),
+        )
+    }
+
+    fn set_center(&mut self, location: Vector2<f32>) {
+        self.x = location.x;
+        self.y = location.y;
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new(0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+/// Keep the view inside of the level, or centered on it when the level is smaller than the view
+fn clamp_axis(center: f32, view_size: f32, level_size: f32) -> f32 {
+    if view_size >= level_size {
+        level_size / 2.0
+    } else {
+        center.clamp(view_size / 2.0, level_size - view_size / 2.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ggez::graphics::Rect;
+
+    use super::*;
+
+    const LEVEL_SIZE: (f32, f32) = (1000.0, 1000.0);
+
+    fn target(x: f32, y: f32) -> CameraTarget {
+        CameraTarget {
+            location: Vector2::new(x, y),
+            velocity: Vector2::new(0.0, 0.0),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_creating_the_camera() {
+        let x = 0.0;
+        let y = 1.0;
+        let width = 50.0;
+        let height = 100.0;
+        let camera = Camera::new(x, y, width, height);
+        assert_eq!(camera.x, x);
+        assert_eq!(camera.y, y);
+        assert_eq!(camera.width, width);
+        assert_eq!(camera.height, height);
+    }
+
+    #[test]
+    fn ci_test_camera_as_rect() {
+        let x = 0.0;
+        let y = 1.0;
+        let width = 50.0;
+        let height = 100.0;
+        let camera = Camera::new(x, y, width, height);
+        let camera_rect: Rect = camera.as_rect();
+        assert_eq!(camera_rect, Rect::new(-25.0, -49.0, 50.0, 100.0));
+    }
+
+    #[test]
+    fn ci_test_camera_without_smoothing_centers_on_the_target() {
+        let mut camera = Camera::new(200.0, 200.0, 100.0, 100.0);
+        camera.update(Some(target(300.0, 250.0)), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(300.0, 250.0));
+    }
+
+    #[test]
+    fn ci_test_camera_does_not_move_while_the_target_is_in_the_dead_zone() {
+        let mut camera = Camera::new(200.0, 200.0, 100.0, 100.0);
+        camera.set_dead_zone(40.0, 20.0);
+        camera.update(Some(target(215.0, 205.0)), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(200.0, 200.0));
+
+        camera.update(Some(target(230.0, 180.0)), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(210.0, 190.0));
+    }
+
+    #[test]
+    fn ci_test_camera_lerps_towards_the_target() {
+        let mut camera = Camera::new(200.0, 200.0, 100.0, 100.0);
+        camera.set_smoothing(CameraSmoothing::Lerp(0.5));
+        camera.update(Some(target(300.0, 200.0)), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(250.0, 200.0));
+        camera.update(Some(target(300.0, 200.0)), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(275.0, 200.0));
+    }
+
+    #[test]
+    fn ci_test_camera_spring_settles_without_overshooting() {
+        let mut camera = Camera::new(200.0, 200.0, 100.0, 100.0);
+        camera.set_smoothing(CameraSmoothing::Spring { frequency: 0.2 });
+        for _ in 0..200 {
+            camera.update(Some(target(300.0, 200.0)), LEVEL_SIZE);
+            assert!(camera.x <= 300.0);
+        }
+        assert!((camera.x - 300.0).abs() < 0.01);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_camera_stays_inside_of_the_level() {
+        let mut camera = Camera::new(200.0, 200.0, 100.0, 100.0);
+        camera.update(Some(target(10.0, 990.0)), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(50.0, 950.0));
+
+        camera.update(Some(target(10.0, 990.0)), (80.0, 2000.0));
+        assert_eq!(camera.x, 40.0);
+    }
+
+    #[test]
+    fn ci_test_camera_transform_interpolates_the_last_movement() {
+        let mut camera = Camera::new(200.0, 200.0, 100.0, 100.0);
+        camera.update(Some(target(210.0, 200.0)), LEVEL_SIZE);
+        assert_eq!(camera.drawn_location(0.0), Vector2::new(200.0, 200.0));
+        assert_eq!(camera.drawn_location(0.5), Vector2::new(205.0, 200.0));
+        assert_eq!(camera.drawn_location(1.0), Vector2::new(210.0, 200.0));
+        assert_eq!(
+            camera.transform(0.5).dest,
+            DrawParam::new().dest([-155.0, -150.0]).dest
+        );
+
+        camera.snap_to(Vector2::new(500.0, 500.0), LEVEL_SIZE);
+        assert_eq!(camera.drawn_location(0.5), Vector2::new(500.0, 500.0));
+    }
+
+    #[test]
+    fn ci_test_camera_looks_ahead_of_a_moving_target() {
+        let mut camera = Camera::new(200.0, 200.0, 100.0, 100.0);
+        camera.set_look_ahead(10.0, 30.0, 5.0);
+        let moving = CameraTarget {
+            location: Vector2::new(300.0, 300.0),
+            velocity: Vector2::new(2.0, -4.0),
+        };
+        camera.update(Some(moving), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(320.0, 295.0));
+    }
+
+    #[test]
+    fn ci_test_zooming_in_shows_less_of_the_level() {
+        let mut camera = Camera::new(500.0, 500.0, 100.0, 100.0);
+        camera.zoom_to(2.0, 0);
+        assert_eq!(camera.view(0.0), Rect::new(475.0, 475.0, 50.0, 50.0));
+        let transform = camera.transform(0.0);
+        assert_eq!(transform.dest, DrawParam::new().dest([-950.0, -950.0]).dest);
+        assert_eq!(transform.scale, DrawParam::new().scale([2.0, 2.0]).scale);
+
+        // zoomed in the camera can get closer to the edge of the level
+        camera.update(Some(target(0.0, 0.0)), LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(25.0, 25.0));
+    }
+
+    #[test]
+    fn ci_test_shake_moves_the_transform_but_not_the_camera() {
+        let mut camera = Camera::new(500.0, 500.0, 100.0, 100.0);
+        camera.set_shake(Shake::new(0.1, 10.0, 10.0));
+        camera.add_trauma(1.0);
+        camera.update(None, LEVEL_SIZE);
+        assert_eq!(Vector2::new(camera.x, camera.y), Vector2::new(500.0, 500.0));
+        assert_ne!(
+            camera.transform(0.0).dest,
+            DrawParam::new().dest([-450.0, -450.0]).dest
+        );
+
+        for _ in 0..10 {
+            camera.update(None, LEVEL_SIZE);
+        }
+        assert_eq!(
+            camera.transform(0.0).dest,
+            DrawParam::new().dest([-450.0, -450.0]).dest
+        );
+    }
+
+    #[test]
+    fn ci_test_screen_to_world() {
+        let mut camera = Camera::new(500.0, 500.0, 100.0, 100.0);
+        assert_eq!(
+            camera.screen_to_world(Vector2::new(0.0, 100.0)),
+            Vector2::new(450.0, 550.0)
+        );
+        camera.zoom_to(2.0, 0);
+        assert_eq!(
+            camera.screen_to_world(Vector2::new(0.0, 100.0)),
+            Vector2::new(475.0, 525.0)
+        );
+    }
+}
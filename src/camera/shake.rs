@@ -0,0 +1,102 @@
+use ggez::nalgebra::Vector2;
+
+/// Trauma based screen shake. Trauma goes from 0.0 to 1.0 and wears off over time, the shake is trauma squared
+/// so that small bumps are subtle and big hits are violent.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Shake {
+    trauma: f32,
+    /// how much trauma wears off each tick
+    decay: f32,
+    max_offset: (f32, f32),
+    offset: Vector2<f32>,
+    tick: u32,
+}
+
+impl Shake {
+    pub fn new(decay: f32, max_offset_x: f32, max_offset_y: f32) -> Self {
+        Self {
+            trauma: 0.0,
+            decay,
+            max_offset: (max_offset_x, max_offset_y),
+            offset: Vector2::new(0.0, 0.0),
+            tick: 0,
+        }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// How far the view is pushed away from where the camera is
+    pub fn offset(&self) -> Vector2<f32> {
+        self.offset
+    }
+
+    /// Pick the next offset and let some of the trauma wear off. The offsets come from the tick count and not
+    /// from a random number generator so that the same updates always shake the same way.
+    pub fn update(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+        let shake = self.trauma * self.trauma;
+        self.offset = Vector2::new(
+            self.max_offset.0 * shake * noise(self.tick, 0),
+            self.max_offset.1 * shake * noise(self.tick, 1),
+        );
+        self.trauma = (self.trauma - self.decay).max(0.0);
+    }
+}
+
+impl Default for Shake {
+    fn default() -> Self {
+        Self::new(0.02, 0.0, 0.0)
+    }
+}
+
+/// A number between -1.0 and 1.0 that looks random but is always the same for the same tick and channel
+fn noise(tick: u32, channel: u32) -> f32 {
+    let mut hash = tick.wrapping_mul(0x9E37_79B9) ^ channel.wrapping_mul(0x85EB_CA6B);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7FEB_352D);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846C_A68B);
+    hash ^= hash >> 16;
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_trauma_wears_off() {
+        let mut shake = Shake::new(0.25, 10.0, 10.0);
+        shake.add_trauma(0.8);
+        shake.add_trauma(0.8);
+        assert_eq!(shake.trauma(), 1.0);
+        for _ in 0..4 {
+            shake.update();
+        }
+        assert_eq!(shake.trauma(), 0.0);
+        shake.update();
+        assert_eq!(shake.offset(), Vector2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn ci_test_shake_stays_within_the_max_offset_and_repeats() {
+        let mut shake = Shake::new(0.0, 10.0, 5.0);
+        let mut same_shake = Shake::new(0.0, 10.0, 5.0);
+        shake.add_trauma(1.0);
+        same_shake.add_trauma(1.0);
+        for _ in 0..100 {
+            shake.update();
+            same_shake.update();
+            assert!(shake.offset().x.abs() <= 10.0);
+            assert!(shake.offset().y.abs() <= 5.0);
+            assert_eq!(shake.offset(), same_shake.offset());
+        }
+    }
+}
@@ -0,0 +1,90 @@
+/// A zoom level that can ease over to a new one over a number of ticks. 1.0 shows the world at its normal size
+/// and 2.0 shows everything twice as big.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Zoom {
+    from: f32,
+    to: f32,
+    ticks: u32,
+    elapsed_ticks: u32,
+}
+
+impl Zoom {
+    pub fn new(zoom: f32) -> Self {
+        Self {
+            from: zoom,
+            to: zoom,
+            ticks: 0,
+            elapsed_ticks: 0,
+        }
+    }
+
+    /// Start easing from the current zoom to the new one, zero ticks changes the zoom right away
+    pub fn zoom_to(&mut self, zoom: f32, ticks: u32) {
+        self.from = self.current();
+        self.to = zoom;
+        self.ticks = ticks;
+        self.elapsed_ticks = 0;
+    }
+
+    pub fn current(&self) -> f32 {
+        if self.elapsed_ticks >= self.ticks {
+            return self.to;
+        }
+        let progress = self.elapsed_ticks as f32 / self.ticks as f32;
+        // smoothstep, so the zoom starts and stops gently
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        self.from + (self.to - self.from) * eased
+    }
+
+    pub fn update(&mut self) {
+        if self.elapsed_ticks < self.ticks {
+            self.elapsed_ticks += 1;
+        }
+    }
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_zoom_eases_to_the_new_zoom() {
+        let mut zoom = Zoom::default();
+        zoom.zoom_to(2.0, 4);
+        assert_eq!(zoom.current(), 1.0);
+        zoom.update();
+        assert_eq!(zoom.current(), 1.15625);
+        zoom.update();
+        assert_eq!(zoom.current(), 1.5);
+        zoom.update();
+        zoom.update();
+        assert_eq!(zoom.current(), 2.0);
+        zoom.update();
+        assert_eq!(zoom.current(), 2.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_zoom_right_away() {
+        let mut zoom = Zoom::default();
+        zoom.zoom_to(0.5, 0);
+        assert_eq!(zoom.current(), 0.5);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_zoom_changed_halfway_starts_from_the_current_zoom() {
+        let mut zoom = Zoom::default();
+        zoom.zoom_to(3.0, 2);
+        zoom.update();
+        zoom.zoom_to(1.0, 2);
+        assert_eq!(zoom.current(), 2.0);
+    }
+}
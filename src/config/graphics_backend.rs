@@ -0,0 +1,31 @@
+use ggez::conf::Backend;
+use serde::{Deserialize, Serialize};
+
+/// The graphics API the window is created with
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GraphicsBackend {
+    OpenGl {
+        major: u8,
+        minor: u8,
+    },
+    /// OpenGL ES, which runs on more machines than OpenGL does
+    Gles {
+        major: u8,
+        minor: u8,
+    },
+}
+
+impl Default for GraphicsBackend {
+    fn default() -> Self {
+        GraphicsBackend::Gles { major: 3, minor: 1 }
+    }
+}
+
+impl From<GraphicsBackend> for Backend {
+    fn from(graphics_backend: GraphicsBackend) -> Self {
+        match graphics_backend {
+            GraphicsBackend::OpenGl { major, minor } => Backend::OpenGL { major, minor },
+            GraphicsBackend::Gles { major, minor } => Backend::OpenGLES { major, minor },
+        }
+    }
+}
@@ -1,5 +1,49 @@
+pub mod graphics_backend;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{bail, eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::CameraSmoothing;
+use crate::viewport::scaling_mode::ScalingMode;
+use crate::world::player_transition::PlayerTransition;
+
+use self::graphics_backend::GraphicsBackend;
+
+/// Environment variables starting with this override the config, `GAME_TARGET_UPDATE_FPS=60` sets
+/// `target_update_fps` for example. The values are written the same way as in the config file. Variables
+/// that do not name a config key are skipped with a warning.
+pub const ENVIRONMENT_PREFIX: &str = "GAME_";
+/// The environment variable with the path to the config file, it is not an override
+pub const CONFIG_PATH_VARIABLE: &str = "GAME_CONFIG";
+/// The config file used when the environment does not name one, the defaults are used if it does not exist
+pub const DEFAULT_CONFIG_PATH: &str = "config.ron";
+
+/// The settings of the game. They can be written in a RON file where every key is optional, for example
+/// ```ron
+/// (
+///     target_update_fps: 60,
+///     scaling_mode: PixelPerfect,
+///     level_paths: ["resources/levels/level_01.ron"],
+/// )
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
+    pub window_width: f32,
+    pub window_height: f32,
+    pub window_maximized: bool,
+    pub window_resizable: bool,
+    pub vsync: bool,
+    pub graphics_backend: GraphicsBackend,
     pub target_update_fps: u32,
+    /// The resolution the game is drawn at before it is scaled to the window
+    pub virtual_width: f32,
+    pub virtual_height: f32,
+    pub scaling_mode: ScalingMode,
     pub gravity_force: f32,
     pub world_width: f32,
     pub world_height: f32,
@@ -9,6 +53,48 @@ pub struct Config {
     pub player_start_y: f32,
     pub player_width: f32,
     pub player_height: f32,
+    pub player_run_force: f32,
+    pub player_max_run_speed: f32,
+    /// How much of the running speed is kept each tick when not running, between 0.0 and 1.0
+    pub player_friction: f32,
+    pub player_jump_force: f32,
+    /// Upwards force applied each tick that jump is held, which is what makes held jumps higher
+    pub player_jump_hold_force: f32,
+    pub player_max_jump_hold_ticks: u32,
+    /// Ticks after walking off of a ledge where jumping is still allowed
+    pub coyote_time_ticks: u32,
+    /// Ticks that a jump pressed in the air is remembered, so that it happens when landing
+    pub jump_buffer_ticks: u32,
+    /// Level files in the order they are played, relative to where the game is started from
+    pub level_paths: Vec<PathBuf>,
+    /// Whether the player stays where they are or goes to the spawn point when the level changes
+    pub player_level_transition: PlayerTransition,
+    /// Sprite atlas files to load, relative to where the game is started from
+    pub sprite_atlas_paths: Vec<PathBuf>,
+    /// The player is drawn as a rectangle when there is no sprite
+    pub player_sprite: Option<String>,
+    /// Animation clips for the player, which replace the player sprite
+    pub player_animations_path: Option<PathBuf>,
+    /// The player can move around this much of the middle of the screen before the camera follows
+    pub camera_dead_zone_width: f32,
+    pub camera_dead_zone_height: f32,
+    pub camera_smoothing: CameraSmoothing,
+    /// How many ticks of the player's velocity the camera looks ahead by, up to the max distances
+    pub camera_look_ahead_ticks: f32,
+    pub camera_max_look_ahead_x: f32,
+    pub camera_max_look_ahead_y: f32,
+    /// How much shake trauma wears off each tick
+    pub camera_shake_decay: f32,
+    pub camera_max_shake_x: f32,
+    pub camera_max_shake_y: f32,
+    /// Start with the debug overlay showing, F3 shows and hides it while playing
+    pub show_debug_overlay: bool,
+    /// Save the input of every update tick and the levels here when the game exits, to reproduce bugs
+    pub record_replay_path: Option<PathBuf>,
+    /// Play this replay instead of taking input, it needs the same config it was recorded with
+    pub play_replay_path: Option<PathBuf>,
+    /// Apply changes to the config and level files while the game is running
+    pub hot_reload: bool,
 }
 
 impl Default for Config {
@@ -21,7 +107,16 @@ impl Default for Config {
         let world_unit_height = 50.0;
 
         Self {
+            window_width: 1280.0,
+            window_height: 720.0,
+            window_maximized: true,
+            window_resizable: true,
+            vsync: false,
+            graphics_backend: GraphicsBackend::default(),
             target_update_fps,
+            virtual_width: 1280.0,
+            virtual_height: 720.0,
+            scaling_mode: ScalingMode::Letterbox,
             gravity_force,
             world_width,
             world_height,
@@ -31,6 +126,277 @@ impl Default for Config {
             player_start_y: 50.0,
             player_width: 50.0,
             player_height: 100.0,
+            player_run_force: 0.3,
+            player_max_run_speed: 4.0,
+            player_friction: 0.8,
+            player_jump_force: 1.2,
+            player_jump_hold_force: 0.009,
+            player_max_jump_hold_ticks: 40,
+            coyote_time_ticks: 6,
+            jump_buffer_ticks: 6,
+            level_paths: vec![PathBuf::from("resources/levels/level_01.ron")],
+            player_level_transition: PlayerTransition::Respawn,
+            sprite_atlas_paths: vec![
+                PathBuf::from("resources/sprites/player.ron"),
+                PathBuf::from("resources/sprites/tiles.ron"),
+            ],
+            player_sprite: Some("player_idle_0".to_owned()),
+            player_animations_path: Some(PathBuf::from("resources/animations/player.ron")),
+            camera_dead_zone_width: 200.0,
+            camera_dead_zone_height: 150.0,
+            camera_smoothing: CameraSmoothing::Lerp(0.1),
+            camera_look_ahead_ticks: 30.0,
+            camera_max_look_ahead_x: 150.0,
+            camera_max_look_ahead_y: 50.0,
+            camera_shake_decay: 0.02,
+            camera_max_shake_x: 30.0,
+            camera_max_shake_y: 30.0,
+            show_debug_overlay: false,
+            record_replay_path: None,
+            play_replay_path: None,
+            hot_reload: true,
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file named by `GAME_CONFIG`, which has to exist, or else `config.ron` if there is one.
+    /// Returns the path so that it can be watched for changes.
+    pub fn load_from_environment() -> Result<(Self, PathBuf)> {
+        match env::var(CONFIG_PATH_VARIABLE) {
+            Ok(path) => Ok((Self::load(&path)?, PathBuf::from(path))),
+            Err(_) => {
+                let path = PathBuf::from(DEFAULT_CONFIG_PATH);
+                let config = if path.exists() {
+                    Self::load(&path)?
+                } else {
+                    Self::from_ron_str_with_overrides("()", environment_overrides(env::vars()))?
+                };
+                Ok((config, path))
+            }
+        }
+    }
+
+    /// Load the config file and apply the environment variable overrides
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let ron = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read config file {}", path.display()))?;
+        Self::from_ron_str_with_overrides(&ron, environment_overrides(env::vars()))
+            .wrap_err_with(|| format!("could not load config file {}", path.display()))
+    }
+
+    /// Parse the config, replace the values of the keys in the overrides and check that the result makes sense
+    pub fn from_ron_str_with_overrides<I: IntoIterator<Item = (String, String)>>(
+        ron: &str,
+        overrides: I,
+    ) -> Result<Self> {
+        let mut config = parse_ron(ron)?;
+        for (key, value) in overrides {
+            config
+                .set(&key, &value)
+                .wrap_err_with(|| format!("could not override config key {}", key))?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Replace the value of one key, the value is written the same way as in the config file
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let mut fields = serde_json::to_value(&*self)?;
+        if fields.get(key).is_none() {
+            bail!("there is no config key {}", key);
         }
+        // parse the value as a config with only this key so that it is read as the right type
+        let value = serde_json::to_value(parse_ron(&format!("({}: {})", key, value))?)?[key].take();
+        fields[key] = value;
+        *self = serde_json::from_value(fields)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.target_update_fps == 0 {
+            bail!("config key target_update_fps must be more than 0");
+        }
+        for (key, value) in [
+            ("world_unit_width", self.world_unit_width),
+            ("world_unit_height", self.world_unit_height),
+            ("world_width", self.world_width),
+            ("world_height", self.world_height),
+            ("virtual_width", self.virtual_width),
+            ("virtual_height", self.virtual_height),
+            ("window_width", self.window_width),
+            ("window_height", self.window_height),
+        ]
+        .iter()
+        {
+            if !(value.is_finite() && *value > 0.0) {
+                bail!("config key {} must be more than 0, it is {}", key, value);
+            }
+        }
+        for (key, size, unit) in [
+            ("world_width", self.world_width, self.world_unit_width),
+            ("world_height", self.world_height, self.world_unit_height),
+        ]
+        .iter()
+        {
+            let units = size / unit;
+            if (units - units.round()).abs() > 0.001 {
+                bail!(
+                    "config key {} must be a multiple of the unit size {}, it is {}",
+                    key,
+                    unit,
+                    size
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The config keys set by environment variables. Other variables with the prefix, such as `GAME_DIR`, are
+/// skipped so that they do not stop the game from starting.
+fn environment_overrides<I: IntoIterator<Item = (String, String)>>(
+    variables: I,
+) -> Vec<(String, String)> {
+    let keys = serde_json::to_value(Config::default()).unwrap_or_default();
+    variables
+        .into_iter()
+        .filter_map(|(name, value)| {
+            if name == CONFIG_PATH_VARIABLE {
+                return None;
+            }
+            let key = name.strip_prefix(ENVIRONMENT_PREFIX)?.to_lowercase();
+            if keys.get(&key).is_none() {
+                eprintln!(
+                    "ignoring environment variable {}, there is no config key {}",
+                    name, key
+                );
+                return None;
+            }
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn parse_ron(ron: &str) -> Result<Config> {
+    ron::de::from_str(ron).map_err(|error| {
+        eyre!(
+            "error in config at line {}, column {}: {}",
+            error.position.line,
+            error.position.col,
+            error.code
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn load(ron: &str, overrides: &[(&str, &str)]) -> Result<Config> {
+        Config::from_ron_str_with_overrides(
+            ron,
+            overrides
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string())),
+        )
+    }
+
+    #[test]
+    fn ci_test_empty_config_is_the_default() {
+        assert_eq!(load("()", &[]).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn ci_test_config_file_in_the_repository_is_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config.ron");
+        let config = load(&fs::read_to_string(path).unwrap(), &[]).unwrap();
+        assert_eq!(
+            config.target_update_fps,
+            Config::default().target_update_fps
+        );
+    }
+
+    #[test]
+    fn ci_test_config_file_with_overrides() {
+        let config = load(
+            "(target_update_fps: 60, scaling_mode: PixelPerfect, vsync: true)",
+            &[
+                ("target_update_fps", "30"),
+                ("camera_smoothing", "Spring(frequency: 2.0)"),
+                ("player_sprite", "None"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.target_update_fps, 30);
+        assert_eq!(config.scaling_mode, ScalingMode::PixelPerfect);
+        assert!(config.vsync);
+        assert_eq!(
+            config.camera_smoothing,
+            CameraSmoothing::Spring { frequency: 2.0 }
+        );
+        assert_eq!(config.player_sprite, None);
+    }
+
+    #[test]
+    fn ci_test_config_errors_name_the_key() {
+        let error = load("(world_unit_width: -5.0)", &[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "config key world_unit_width must be more than 0, it is -5"
+        );
+
+        let error = load("(world_width: 5010.0)", &[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "config key world_width must be a multiple of the unit size 50, it is 5010"
+        );
+
+        let error = load("()", &[("target_update_fps", "0")]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "config key target_update_fps must be more than 0"
+        );
+
+        let error = load("()", &[("jump_height", "5")]).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "could not override config key jump_height: there is no config key jump_height"
+        );
+
+        let error = load("(vsync: maybe)", &[]).unwrap_err();
+        assert!(
+            error.to_string().starts_with("error in config at line 1"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn ci_test_a_missing_config_file_is_an_error() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("no_such_config.ron");
+        let error = Config::load(&path).unwrap_err();
+        assert!(
+            error.to_string().starts_with("could not read config file"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn ci_test_environment_variables_that_are_not_config_keys_are_skipped() {
+        let variables = [
+            ("GAME_TARGET_UPDATE_FPS", "30"),
+            ("GAME_DIR", "/games"),
+            ("GAME_CONFIG", "other.ron"),
+            ("HOME", "/root"),
+        ]
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        assert_eq!(
+            environment_overrides(variables),
+            vec![("target_update_fps".to_owned(), "30".to_owned())]
+        );
     }
 }
@@ -0,0 +1,121 @@
+pub mod update_rate;
+
+use std::time::Duration;
+
+use ggez::graphics::{
+    self, apply_transformations, pop_transform, push_transform, Color, DrawMode, DrawParam,
+    MeshBuilder, Rect, Text,
+};
+use ggez::nalgebra::Point2;
+use ggez::{timer, Context, GameResult};
+
+use crate::world::World;
+
+use self::update_rate::UpdateRate;
+
+const ENTITY_COLOR: Color = Color::new(0.0, 1.0, 0.0, 1.0);
+const GRID_CELL_COLOR: Color = Color::new(0.0, 0.5, 1.0, 0.5);
+const VELOCITY_COLOR: Color = Color::new(1.0, 0.0, 0.0, 1.0);
+const CAMERA_COLOR: Color = Color::new(1.0, 1.0, 0.0, 1.0);
+/// velocities are per tick, which is too short to see, so draw how far the entity would move in this many ticks
+const VELOCITY_TICKS: f32 = 10.0;
+
+/// Draws what the game is doing on top of the world: the bounding boxes of the entities, the grid cells that
+/// have something in them, velocities, entity states, the camera and how fast we are updating and drawing
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+    update_rate: UpdateRate,
+}
+
+impl DebugOverlay {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Call once per fixed update with the time since the game started
+    pub fn record_update(&mut self, now: Duration) {
+        self.update_rate.record(now);
+    }
+
+    pub fn draw(&self, context: &mut Context, world: &World, lag: f32) -> GameResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let camera = world.camera();
+        let view = camera.view(lag);
+        let entities = world.entities_near(view);
+        let stroke = DrawMode::stroke(1.0 / camera.zoom());
+        let mut mesh_builder = MeshBuilder::new();
+        for cell in world.occupied_grid_cells(view) {
+            mesh_builder.rectangle(stroke, cell, GRID_CELL_COLOR);
+        }
+        for entity in entities.iter() {
+            let location = entity.interpolated_location(lag);
+            let bounding_box = Rect::new(
+                location.x - entity.width / 2.0,
+                location.y - entity.height / 2.0,
+                entity.width,
+                entity.height,
+            );
+            mesh_builder.rectangle(stroke, bounding_box, ENTITY_COLOR);
+            let velocity = entity.velocity();
+            if velocity.x != 0.0 || velocity.y != 0.0 {
+                let end = location + velocity * VELOCITY_TICKS;
+                mesh_builder.line(
+                    &[
+                        Point2::new(location.x, location.y),
+                        Point2::new(end.x, end.y),
+                    ],
+                    2.0 / camera.zoom(),
+                    VELOCITY_COLOR,
+                )?;
+            }
+        }
+        let mut camera_rect = view;
+        // inset so the edges are on screen
+        camera_rect.translate([1.0, 1.0]);
+        camera_rect.w -= 2.0;
+        camera_rect.h -= 2.0;
+        mesh_builder.rectangle(stroke, camera_rect, CAMERA_COLOR);
+
+        push_transform(context, Some(camera.transform(lag).to_matrix()));
+        apply_transformations(context)?;
+        let mesh = mesh_builder.build(context)?;
+        graphics::draw(context, &mesh, DrawParam::new())?;
+        // only the entities that move have a state worth showing
+        for entity in entities
+            .iter()
+            .filter(|entity| entity.physics_system.is_some())
+        {
+            let location = entity.interpolated_location(lag);
+            let label = Text::new(format!("{:?}", entity.get_state()));
+            let dest = [
+                location.x - entity.width / 2.0,
+                location.y - entity.height / 2.0 - 16.0,
+            ];
+            graphics::draw(
+                context,
+                &label,
+                DrawParam::new().dest(dest).color(ENTITY_COLOR),
+            )?;
+        }
+        pop_transform(context);
+        apply_transformations(context)?;
+
+        let rates = Text::new(format!(
+            "update: {} fps\ndraw: {:.0} fps",
+            self.update_rate.per_second(),
+            timer::fps(context)
+        ));
+        graphics::draw(context, &rates, DrawParam::new().dest([10.0, 10.0]))
+    }
+}
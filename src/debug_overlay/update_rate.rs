@@ -0,0 +1,48 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Counts how many fixed updates ran in the last second. ggez only measures how often we draw.
+#[derive(Debug, Default)]
+pub struct UpdateRate {
+    update_times: VecDeque<Duration>,
+}
+
+impl UpdateRate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call once per fixed update with the time since the game started
+    pub fn record(&mut self, now: Duration) {
+        self.update_times.push_back(now);
+        while self
+            .update_times
+            .front()
+            .is_some_and(|time| now.saturating_sub(*time) >= WINDOW)
+        {
+            self.update_times.pop_front();
+        }
+    }
+
+    pub fn per_second(&self) -> usize {
+        self.update_times.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ci_test_update_rate_counts_the_last_second() {
+        let mut update_rate = UpdateRate::new();
+        assert_eq!(update_rate.per_second(), 0);
+        for tick in 0..120 {
+            update_rate.record(Duration::from_millis(tick * 20));
+        }
+        // 50 updates a second at 20ms each
+        assert_eq!(update_rate.per_second(), 50);
+    }
+}
@@ -3,21 +3,35 @@ use std::fmt::Debug;
 use ggez::{Context, GameResult};
 
 use crate::drawables::Drawables;
+use crate::entity::entity_state::EntityState;
 use crate::physics_system::PhysicsSystem;
 
+pub mod platform_draw_system;
 pub mod player_draw_system;
 
 pub trait DrawSystem
 where
     Self: Debug,
 {
+    /// The location is already interpolated between the last two updates, so it is where the entity is on screen
     fn draw(
         &self,
         drawables: &Drawables,
         context: &mut Context,
         location: &ggez::nalgebra::Vector2<f32>,
         size: (f32, f32),
-        lag: f32,
         physics_system: &Option<Box<dyn PhysicsSystem>>,
     ) -> GameResult;
+    /// Called once per update tick after the entity has moved, so that animations play at the same speed no
+    /// matter how fast we are drawing
+    fn update(&mut self, _state: EntityState, _physics_system: &Option<Box<dyn PhysicsSystem>>) {}
+    /// Animation events, such as footsteps, since the last time they were taken
+    fn take_events(&mut self) -> Vec<String> {
+        vec![]
+    }
+    /// A sprite that never changes. Entities that do not move draw these together in a sprite batch instead of
+    /// one at a time.
+    fn batched_sprite(&self) -> Option<&str> {
+        None
+    }
 }
@@ -0,0 +1,57 @@
+use ggez::graphics::{draw, Color, DrawParam};
+use ggez::{Context, GameResult};
+
+use crate::drawables::{Drawables, SpriteDrawing};
+use crate::physics_system::PhysicsSystem;
+
+use super::DrawSystem;
+
+/// Draws an entity that does not animate with the color and sprite from the level, the color is used when
+/// there is no sprite or when the sprite has not been loaded
+#[derive(Debug)]
+pub struct PlatformDrawSystem {
+    color: Color,
+    sprite: Option<String>,
+}
+
+impl PlatformDrawSystem {
+    pub fn new(color: Color, sprite: Option<String>) -> Self {
+        Self { color, sprite }
+    }
+}
+
+impl DrawSystem for PlatformDrawSystem {
+    fn draw(
+        &self,
+        drawables: &Drawables,
+        context: &mut Context,
+        location: &ggez::nalgebra::Vector2<f32>,
+        (width, height): (f32, f32),
+        _physics_system: &Option<Box<dyn PhysicsSystem>>,
+    ) -> GameResult {
+        match &self.sprite {
+            Some(sprite) if drawables.has_sprite(sprite) => drawables.draw_sprite(
+                context,
+                sprite,
+                SpriteDrawing {
+                    center: *location,
+                    size: (width, height),
+                    flip_x: false,
+                    flip_y: false,
+                },
+            ),
+            _ => draw(
+                context,
+                &drawables.platform,
+                DrawParam::new()
+                    .dest([location.x, location.y])
+                    .scale([width, height])
+                    .color(self.color),
+            ),
+        }
+    }
+
+    fn batched_sprite(&self) -> Option<&str> {
+        self.sprite.as_deref()
+    }
+}
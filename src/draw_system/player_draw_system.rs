@@ -1,13 +1,42 @@
-use ggez::graphics::{draw, DrawMode, DrawParam, MeshBuilder, Rect, WHITE};
+use ggez::graphics::{draw, DrawParam};
 use ggez::{Context, GameResult};
 
-use crate::drawables::Drawables;
+use crate::animation::Animator;
+use crate::drawables::{Drawables, SpriteDrawing};
+use crate::entity::entity_state::EntityState;
 use crate::physics_system::PhysicsSystem;
 
 use super::DrawSystem;
 
-#[derive(Debug)]
-pub struct PlayerDrawSystem;
+/// Draws the player with its sprite, or as a rectangle when there is no sprite
+#[derive(Debug, Default)]
+pub struct PlayerDrawSystem {
+    sprite: Option<String>,
+    animator: Option<Animator>,
+    facing_left: bool,
+}
+
+impl PlayerDrawSystem {
+    pub fn new(sprite: Option<String>) -> Self {
+        Self {
+            sprite,
+            ..Self::default()
+        }
+    }
+
+    /// Draw the frames of the animator instead of the still sprite
+    pub fn with_animator(mut self, animator: Animator) -> Self {
+        self.animator = Some(animator);
+        self
+    }
+
+    fn current_sprite(&self) -> Option<&str> {
+        self.animator
+            .as_ref()
+            .and_then(|animator| animator.current_sprite())
+            .or(self.sprite.as_deref())
+    }
+}
 
 impl DrawSystem for PlayerDrawSystem {
     fn draw(
@@ -16,37 +45,47 @@ impl DrawSystem for PlayerDrawSystem {
         context: &mut Context,
         location: &ggez::nalgebra::Vector2<f32>,
         (width, height): (f32, f32),
-        lag: f32,
-        physics_system: &Option<Box<dyn PhysicsSystem>>,
+        _physics_system: &Option<Box<dyn PhysicsSystem>>,
     ) -> GameResult {
-        let mut x = location.x;
-        let mut y = location.y;
-        if let Some(physics_system) = physics_system {
-            let part_velocity = physics_system.get_velocity() * lag;
-            x += part_velocity.x;
-            y += part_velocity.y;
+        match self.current_sprite() {
+            Some(sprite) if drawables.has_sprite(sprite) => drawables.draw_sprite(
+                context,
+                sprite,
+                SpriteDrawing {
+                    center: *location,
+                    size: (width, height),
+                    flip_x: self.facing_left,
+                    flip_y: false,
+                },
+            ),
+            _ => draw(
+                context,
+                &drawables.player,
+                DrawParam::default().dest([location.x, location.y]),
+            ),
+        }
+    }
+
+    fn update(&mut self, state: EntityState, physics_system: &Option<Box<dyn PhysicsSystem>>) {
+        let velocity = match physics_system {
+            Some(physics_system) => *physics_system.get_velocity(),
+            None => ggez::nalgebra::Vector2::new(0.0, 0.0),
+        };
+        // keep facing the same way when stopping
+        if velocity.x < 0.0 {
+            self.facing_left = true;
+        } else if velocity.x > 0.0 {
+            self.facing_left = false;
+        }
+        if let Some(animator) = &mut self.animator {
+            animator.update(state, &velocity);
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<String> {
+        match &mut self.animator {
+            Some(animator) => animator.take_events(),
+            None => vec![],
         }
-        draw(
-            context,
-            &drawables.player,
-            DrawParam::default().dest([x, y]),
-        )?;
-
-        let border = MeshBuilder::new()
-            .rectangle(
-                DrawMode::stroke(2.0),
-                Rect::new(
-                    location.x - width / 2.0,
-                    location.y - height / 2.0,
-                    width,
-                    height,
-                ),
-                WHITE,
-            )
-            .build(context)?;
-
-        draw(context, &border, DrawParam::new())?;
-
-        Ok(())
     }
 }
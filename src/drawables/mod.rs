@@ -0,0 +1,249 @@
+pub mod sprite_atlas;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use eyre::{bail, eyre, Result, WrapErr};
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::graphics::{draw, Color, DrawMode, DrawParam, Image, Mesh, MeshBuilder, Rect, WHITE};
+use ggez::nalgebra::Vector2;
+use ggez::{Context, GameError, GameResult};
+
+use crate::config::Config;
+
+use self::sprite_atlas::{Sprite, SpriteAtlas};
+
+pub struct Drawables {
+    pub player: Mesh,
+    pub platform: Mesh,
+    images: HashMap<String, Image>,
+    sprites: HashMap<String, Sprite>,
+    /// one batch per z and image with its z, back to front and then by image name, so that all of the tiles in a
+    /// level are drawn in a few calls and in the same order every time
+    tile_batches: Vec<(i32, SpriteBatch)>,
+}
+
+/// Where and how to draw a sprite, the sprite is stretched to fill the size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteDrawing {
+    pub center: Vector2<f32>,
+    pub size: (f32, f32),
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Drawables {
+    pub fn new(context: &mut Context, config: &Config) -> GameResult<Self> {
+        let player = MeshBuilder::new()
+            .rectangle(
+                DrawMode::fill(),
+                Rect::new(
+                    -config.player_width / 2.0,
+                    -config.player_height / 2.0,
+                    config.player_width,
+                    config.player_height,
+                ),
+                Color::new(0.1, 0.1, 1.0, 1.0),
+            )
+            .build(context)?;
+
+        let platform = Self::create_platform(context)?;
+
+        Ok(Self {
+            player,
+            platform,
+            images: HashMap::new(),
+            sprites: HashMap::new(),
+            tile_batches: vec![],
+        })
+    }
+
+    /// A white square one pixel in size around the origin, which is scaled to the size of the platform and tinted
+    /// with its color
+    fn create_platform(context: &mut Context) -> GameResult<Mesh> {
+        MeshBuilder::new()
+            .rectangle(DrawMode::fill(), Rect::new(-0.5, -0.5, 1.0, 1.0), WHITE)
+            .build(context)
+    }
+
+    /// Load a sprite atlas file and its image. The atlas is named after the file, so `sprites/player.ron` is
+    /// `player`, and its sprites are looked up by the names in the file.
+    pub fn load_atlas<P: AsRef<Path>>(&mut self, context: &mut Context, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| eyre!("could not name the sprite atlas {}", path.display()))?;
+        let atlas = fs::read_to_string(path)
+            .wrap_err("could not read the file")
+            .and_then(|contents| SpriteAtlas::from_ron_str(&contents))
+            .and_then(|atlas| {
+                let image = Image::new(context, &atlas.image)
+                    .map_err(|error| eyre!("could not load {}: {}", atlas.image, error))?;
+                Ok((atlas, image))
+            });
+
+        atlas
+            .and_then(|(atlas, image)| self.add_atlas(name, image, &atlas))
+            .wrap_err_with(|| format!("could not load sprite atlas {}", path.display()))
+    }
+
+    pub fn add_atlas(&mut self, name: &str, image: Image, atlas: &SpriteAtlas) -> Result<()> {
+        for (key, source) in atlas.sprite_rects()? {
+            if self.sprites.contains_key(&key) {
+                bail!("there is already a sprite named {}", key);
+            }
+            let sprite = Sprite {
+                atlas: name.to_owned(),
+                source,
+            };
+            self.sprites.insert(key, sprite);
+        }
+        self.images.insert(name.to_owned(), image);
+        Ok(())
+    }
+
+    pub fn has_sprite(&self, key: &str) -> bool {
+        self.sprites.contains_key(key)
+    }
+
+    pub fn draw_sprite(
+        &self,
+        context: &mut Context,
+        key: &str,
+        drawing: SpriteDrawing,
+    ) -> GameResult {
+        let (sprite, image) = self
+            .sprite_and_image(key)
+            .map_err(|error| GameError::RenderError(error.to_string()))?;
+        draw(
+            context,
+            image,
+            sprite_draw_param(sprite, image_size(image), drawing),
+        )
+    }
+
+    /// Replace the static tiles of the level with these sprites, each one stretched over its rect in the world and
+    /// drawn with its z. Tiles whose sprite is not loaded are left out.
+    pub fn set_tiles(&mut self, tiles: &[(String, Rect, i32)]) -> Result<()> {
+        let mut batches: BTreeMap<(i32, &str), SpriteBatch> = BTreeMap::new();
+        for (key, rect, z) in tiles.iter().filter(|(key, _, _)| self.has_sprite(key)) {
+            let (sprite, image) = self.sprite_and_image(key)?;
+            let drawing = SpriteDrawing {
+                center: Vector2::new(rect.x + rect.w / 2.0, rect.y + rect.h / 2.0),
+                size: (rect.w, rect.h),
+                flip_x: false,
+                flip_y: false,
+            };
+            batches
+                .entry((*z, &sprite.atlas))
+                .or_insert_with(|| SpriteBatch::new(image.clone()))
+                .add(sprite_draw_param(sprite, image_size(image), drawing));
+        }
+        self.tile_batches = batches
+            .into_iter()
+            .map(|((z, _), batch)| (z, batch))
+            .collect();
+        Ok(())
+    }
+
+    /// The z values that have tiles, back to front
+    pub fn tile_zs(&self) -> Vec<i32> {
+        let mut zs: Vec<i32> = self.tile_batches.iter().map(|(z, _)| *z).collect();
+        zs.dedup();
+        zs
+    }
+
+    /// Draw the tiles with the z
+    pub fn draw_tiles(&self, context: &mut Context, z: i32) -> GameResult {
+        self.tile_batches
+            .iter()
+            .filter(|(batch_z, _)| *batch_z == z)
+            .try_for_each(|(_, batch)| draw(context, batch, DrawParam::new()))
+    }
+
+    fn sprite_and_image(&self, key: &str) -> Result<(&Sprite, &Image)> {
+        let sprite = self
+            .sprites
+            .get(key)
+            .ok_or_else(|| eyre!("there is no sprite named {}", key))?;
+        let image = self
+            .images
+            .get(&sprite.atlas)
+            .ok_or_else(|| eyre!("the image for sprite atlas {} is missing", sprite.atlas))?;
+        Ok((sprite, image))
+    }
+}
+
+fn image_size(image: &Image) -> (f32, f32) {
+    (f32::from(image.width()), f32::from(image.height()))
+}
+
+/// Flipping is done with a negative scale, which mirrors the sprite around its left or top edge, so the
+/// destination moves to the other edge to keep the sprite in place
+fn sprite_draw_param(sprite: &Sprite, image_size: (f32, f32), drawing: SpriteDrawing) -> DrawParam {
+    let (width, height) = drawing.size;
+    let mut scale = Vector2::new(width / sprite.source.w, height / sprite.source.h);
+    let mut dest = drawing.center - Vector2::new(width / 2.0, height / 2.0);
+    if drawing.flip_x {
+        scale.x = -scale.x;
+        dest.x += width;
+    }
+    if drawing.flip_y {
+        scale.y = -scale.y;
+        dest.y += height;
+    }
+
+    DrawParam::new()
+        .src(sprite.uv(image_size))
+        .dest([dest.x, dest.y])
+        .scale([scale.x, scale.y])
+}
+
+#[cfg(test)]
+mod test {
+    use ggez::mint::{Point2, Vector2 as MintVector2};
+
+    use super::*;
+
+    fn sprite() -> Sprite {
+        Sprite {
+            atlas: "player".to_owned(),
+            source: Rect::new(16.0, 0.0, 16.0, 32.0),
+        }
+    }
+
+    #[test]
+    fn ci_test_sprite_draw_param_stretches_over_the_entity() {
+        let drawing = SpriteDrawing {
+            center: Vector2::new(100.0, 100.0),
+            size: (50.0, 100.0),
+            flip_x: false,
+            flip_y: false,
+        };
+        let param = sprite_draw_param(&sprite(), (64.0, 32.0), drawing);
+        assert_eq!(param.src, Rect::new(0.25, 0.0, 0.25, 1.0));
+        assert_eq!(param.dest, Point2 { x: 75.0, y: 50.0 });
+        assert_eq!(param.scale, MintVector2 { x: 3.125, y: 3.125 });
+    }
+
+    #[test]
+    fn ci_test_flipped_sprites_stay_in_place() {
+        let drawing = SpriteDrawing {
+            center: Vector2::new(100.0, 100.0),
+            size: (50.0, 100.0),
+            flip_x: true,
+            flip_y: true,
+        };
+        let param = sprite_draw_param(&sprite(), (64.0, 32.0), drawing);
+        assert_eq!(param.dest, Point2 { x: 125.0, y: 150.0 });
+        assert_eq!(
+            param.scale,
+            MintVector2 {
+                x: -3.125,
+                y: -3.125
+            }
+        );
+    }
+}
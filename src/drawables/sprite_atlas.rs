@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use eyre::{bail, eyre, Result};
+use ggez::graphics::Rect;
+use serde::{Deserialize, Serialize};
+
+/// Describes where the sprites are in an image, written in RON, for example
+/// ```ron
+/// (
+///     image: "/sprites/tiles.png",
+///     sprites: {"grass": (0.0, 0.0, 16.0, 16.0)},
+/// )
+/// ```
+/// Sheets where every frame is the same size can use a grid instead of listing every rectangle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteAtlas {
+    /// path to the image in the ggez resources folder
+    pub image: String,
+    /// x, y, width and height in pixels
+    #[serde(default)]
+    pub sprites: BTreeMap<String, (f32, f32, f32, f32)>,
+    #[serde(default)]
+    pub grid: Option<SpriteGrid>,
+}
+
+/// Frames laid out left to right and then top to bottom, named in that order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteGrid {
+    pub frame_width: f32,
+    pub frame_height: f32,
+    pub columns: u32,
+    pub names: Vec<String>,
+}
+
+/// A named part of a loaded image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sprite {
+    /// the name of the atlas that the image was loaded for
+    pub atlas: String,
+    /// where the sprite is in the image, in pixels
+    pub source: Rect,
+}
+
+impl Sprite {
+    /// The source rect in the 0.0 to 1.0 range that ggez expects
+    pub fn uv(&self, (image_width, image_height): (f32, f32)) -> Rect {
+        Rect::new(
+            self.source.x / image_width,
+            self.source.y / image_height,
+            self.source.w / image_width,
+            self.source.h / image_height,
+        )
+    }
+}
+
+impl SpriteAtlas {
+    pub fn from_ron_str(ron: &str) -> Result<Self> {
+        ron::de::from_str(ron).map_err(|error| {
+            eyre!(
+                "error in sprite atlas at line {}, column {}: {}",
+                error.position.line,
+                error.position.col,
+                error.code
+            )
+        })
+    }
+
+    /// Every sprite in the atlas with where it is in the image, in pixels
+    pub fn sprite_rects(&self) -> Result<BTreeMap<String, Rect>> {
+        let mut rects: BTreeMap<String, Rect> = self
+            .sprites
+            .iter()
+            .map(|(name, &(x, y, width, height))| (name.clone(), Rect::new(x, y, width, height)))
+            .collect();
+
+        if let Some(grid) = &self.grid {
+            if grid.columns == 0 {
+                bail!("sprite grid in {} needs at least one column", self.image);
+            }
+            for (index, name) in grid.names.iter().enumerate() {
+                let column = index as u32 % grid.columns;
+                let row = index as u32 / grid.columns;
+                let rect = Rect::new(
+                    column as f32 * grid.frame_width,
+                    row as f32 * grid.frame_height,
+                    grid.frame_width,
+                    grid.frame_height,
+                );
+                if rects.insert(name.clone(), rect).is_some() {
+                    bail!("sprite {} is in {} more than once", name, self.image);
+                }
+            }
+        }
+
+        Ok(rects)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ci_test_sprite_rects_from_rects_and_grid() {
+        let atlas = SpriteAtlas::from_ron_str(
+            r#"(
+                image: "/sprites/player.png",
+                sprites: {"icon": (0.0, 64.0, 8.0, 8.0)},
+                grid: Some((frame_width: 16.0, frame_height: 32.0, columns: 2, names: ["a", "b", "c"])),
+            )"#,
+        )
+        .unwrap();
+        let rects = atlas.sprite_rects().unwrap();
+        assert_eq!(rects["icon"], Rect::new(0.0, 64.0, 8.0, 8.0));
+        assert_eq!(rects["b"], Rect::new(16.0, 0.0, 16.0, 32.0));
+        assert_eq!(rects["c"], Rect::new(0.0, 32.0, 16.0, 32.0));
+    }
+
+    #[test]
+    fn ci_test_duplicate_sprite_names_are_errors() {
+        let atlas = SpriteAtlas::from_ron_str(
+            r#"(
+                image: "/sprites/player.png",
+                sprites: {"a": (0.0, 0.0, 8.0, 8.0)},
+                grid: Some((frame_width: 16.0, frame_height: 32.0, columns: 2, names: ["a"])),
+            )"#,
+        )
+        .unwrap();
+        let error = atlas.sprite_rects().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "sprite a is in /sprites/player.png more than once"
+        );
+    }
+
+    #[test]
+    fn ci_test_sprite_uv() {
+        let sprite = Sprite {
+            atlas: "tiles".to_owned(),
+            source: Rect::new(16.0, 0.0, 16.0, 16.0),
+        };
+        assert_eq!(sprite.uv((32.0, 16.0)), Rect::new(0.5, 0.0, 0.5, 1.0));
+    }
+
+    #[test]
+    fn ci_test_atlas_files_in_resources_are_valid() {
+        for name in ["player", "tiles"].iter() {
+            let path = format!(
+                "{}/resources/sprites/{}.ron",
+                env!("CARGO_MANIFEST_DIR"),
+                name
+            );
+            let atlas = SpriteAtlas::from_ron_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+            assert!(!atlas.sprite_rects().unwrap().is_empty());
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use ggez::nalgebra::Vector2;
 
+use crate::ai_system::AiSystem;
 use crate::draw_system::DrawSystem;
 use crate::physics_system::PhysicsSystem;
 use crate::Entity;
 
 use super::entity_state::EntityState;
+use super::entity_type::EntityType;
+use super::render_layer::RenderLayer;
 
 pub struct EntityBuilder {
     next_id: u32,
@@ -14,23 +17,18 @@ pub struct EntityBuilder {
     draw_system: Option<Box<dyn DrawSystem>>,
     affected_by_gravity: bool,
     physics_system: Option<Box<dyn PhysicsSystem>>,
+    ai_system: Option<Box<dyn AiSystem>>,
     collidable: bool,
     state: EntityState,
+    player_controlled: bool,
+    entity_type: Option<EntityType>,
+    layer: RenderLayer,
+    z: i32,
 }
 
 impl EntityBuilder {
     pub fn new() -> Self {
-        Self {
-            next_id: 0,
-            location: Vector2::new(0.0, 0.0),
-            width: 0.0,
-            height: 0.0,
-            draw_system: None,
-            affected_by_gravity: false,
-            physics_system: None,
-            collidable: false,
-            state: EntityState::None,
-        }
+        Self::default()
     }
 
     pub fn create_entity(&mut self) -> &mut Self {
@@ -41,14 +39,20 @@ impl EntityBuilder {
         let id = self.next_id;
         let entity = Entity {
             location: self.location,
+            previous_location: self.location,
             width: self.width,
             height: self.height,
             draw_system: self.draw_system.take(),
             affected_by_gravity: self.affected_by_gravity,
             physics_system: self.physics_system.take(),
+            ai_system: self.ai_system.take(),
             collidable: self.collidable,
             state: self.state,
             id,
+            player_controlled: self.player_controlled,
+            entity_type: self.entity_type.take(),
+            layer: self.layer,
+            z: self.z,
         };
         self.reset();
         entity
@@ -78,8 +82,13 @@ impl EntityBuilder {
         self.height = 0.0;
         self.draw_system = None;
         self.affected_by_gravity = false;
+        self.ai_system = None;
         self.collidable = false;
         self.state = EntityState::None;
+        self.player_controlled = false;
+        self.entity_type = None;
+        self.layer = RenderLayer::default();
+        self.z = 0;
     }
 
     pub fn affected_by_gravity(&mut self) -> &mut Self {
@@ -92,6 +101,12 @@ impl EntityBuilder {
         self
     }
 
+    /// Entities with an AI system decide for themselves where their physics system moves them
+    pub fn ai_system(&mut self, ai_system: Box<dyn AiSystem>) -> &mut Self {
+        self.ai_system = Some(ai_system);
+        self
+    }
+
     pub fn collidable(&mut self) -> &mut Self {
         self.collidable = true;
         self
@@ -101,12 +116,62 @@ impl EntityBuilder {
         self.state = state;
         self
     }
+
+    /// The entity is moved by the input of the person playing the game
+    pub fn player_controlled(&mut self) -> &mut Self {
+        self.player_controlled = true;
+        self
+    }
+
+    pub fn entity_type(&mut self, entity_type: EntityType) -> &mut Self {
+        self.entity_type = Some(entity_type);
+        self
+    }
+
+    pub fn layer(&mut self, layer: RenderLayer) -> &mut Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Entities with a higher z are drawn on top of the others in the same layer
+    pub fn z(&mut self, z: i32) -> &mut Self {
+        self.z = z;
+        self
+    }
+}
+
+impl Default for EntityBuilder {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            location: Vector2::new(0.0, 0.0),
+            width: 0.0,
+            height: 0.0,
+            draw_system: None,
+            affected_by_gravity: false,
+            physics_system: None,
+            ai_system: None,
+            collidable: false,
+            state: EntityState::None,
+            player_controlled: false,
+            entity_type: None,
+            layer: RenderLayer::default(),
+            z: 0,
+        }
+    }
 }
 
 #[cfg(test)]
+// these tests predate the lints below and are kept as they were written
+#[allow(
+    unused_variables,
+    clippy::bool_assert_comparison,
+    clippy::redundant_pattern_matching
+)]
 mod test {
     use ggez::nalgebra::Vector2;
 
+    use crate::ai_system::ledge_walker::LedgeWalker;
     use crate::draw_system::player_draw_system::PlayerDrawSystem;
     use crate::physics_system::player_physics_system::PlayerPhysicsSystem;
     use crate::Entity;
@@ -166,7 +231,7 @@ mod test {
     #[test]
     #[allow(clippy::float_cmp)]
     fn ci_test_create_entity_with_draw_system() {
-        let draw_system = PlayerDrawSystem;
+        let draw_system = PlayerDrawSystem::default();
         let mut entity_builder = EntityBuilder::new();
         let entity = entity_builder
             .create_entity()
@@ -184,11 +249,16 @@ mod test {
             .create_entity()
             .location(15.0, 156.0)
             .size(25.0, 15.0)
-            .draw_system(Box::new(PlayerDrawSystem))
+            .draw_system(Box::new(PlayerDrawSystem::default()))
             .affected_by_gravity()
             .physics_system(Box::new(PlayerPhysicsSystem::default()))
+            .ai_system(Box::new(LedgeWalker::new(1.0)))
             .collidable()
             .state(EntityState::Standing)
+            .player_controlled()
+            .entity_type(EntityType::Goal)
+            .layer(RenderLayer::Foreground)
+            .z(3)
             .build();
 
         assert_eq!(entity_builder.next_id, 1);
@@ -198,8 +268,23 @@ mod test {
         assert!(matches!(entity_builder.draw_system, None));
         assert_eq!(entity_builder.affected_by_gravity, false);
         assert!(entity_builder.physics_system.is_none());
+        assert!(entity_builder.ai_system.is_none());
         assert_eq!(entity_builder.collidable, false);
         assert!(matches!(entity_builder.state, EntityState::None));
+        assert!(!entity_builder.player_controlled);
+        assert!(entity_builder.entity_type.is_none());
+        assert_eq!(entity_builder.layer, RenderLayer::Entities);
+        assert_eq!(entity_builder.z, 0);
+    }
+
+    #[test]
+    fn ci_test_create_entity_with_entity_type() {
+        let mut entity_builder = EntityBuilder::new();
+        let entity = entity_builder
+            .create_entity()
+            .entity_type(EntityType::Platform)
+            .build();
+        assert_eq!(entity.entity_type, Some(EntityType::Platform));
     }
 
     #[test]
@@ -235,4 +320,11 @@ mod test {
             .build();
         assert!(matches!(entity.state, EntityState::Falling));
     }
+
+    #[test]
+    fn ci_test_entity_builder_should_create_player_controlled_entity() {
+        let mut entity_builder = EntityBuilder::new();
+        let entity = entity_builder.create_entity().player_controlled().build();
+        assert!(entity.player_controlled);
+    }
 }
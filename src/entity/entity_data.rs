@@ -1,15 +1,35 @@
 use ggez::graphics::{Color, WHITE};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_system::behavior::Behavior;
 
 use super::entity_type::EntityType;
+use super::render_layer::RenderLayer;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct EntityData {
     pub width: f32,
     pub height: f32,
     pub x: f32,
     pub y: f32,
+    #[serde(with = "color_format", default = "color_format::default")]
     pub color: Color,
     pub entity_type: EntityType,
+    /// drawn instead of the color when the sprite is loaded
+    #[serde(default)]
+    pub sprite: Option<String>,
+    /// the layer to draw in, platforms default to the tile layer and everything else to the entity layer
+    #[serde(default)]
+    pub layer: Option<RenderLayer>,
+    /// entities with a higher z are drawn on top of the others in the same layer
+    #[serde(default)]
+    pub z: i32,
+    /// the name of one of the level's item kinds, items need one
+    #[serde(default)]
+    pub item_kind: Option<String>,
+    /// how an enemy moves, enemies without one stay where they are
+    #[serde(default)]
+    pub behavior: Option<Behavior>,
 }
 
 impl EntityData {
@@ -28,8 +48,18 @@ impl EntityData {
             y,
             color,
             entity_type,
+            sprite: None,
+            layer: None,
+            z: 0,
+            item_kind: None,
+            behavior: None,
         }
     }
+
+    pub fn layer(&self) -> RenderLayer {
+        self.layer
+            .unwrap_or_else(|| self.entity_type.default_layer())
+    }
 }
 
 impl Default for EntityData {
@@ -41,10 +71,34 @@ impl Default for EntityData {
             y: 0.0,
             color: WHITE,
             entity_type: EntityType::Platform,
+            sprite: None,
+            layer: None,
+            z: 0,
+            item_kind: None,
+            behavior: None,
         }
     }
 }
 
+/// Colors are stored in level files as an (r, g, b, a) tuple with values between 0.0 and 1.0
+mod color_format {
+    use ggez::graphics::{Color, WHITE};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        (color.r, color.g, color.b, color.a).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let (r, g, b, a) = <(f32, f32, f32, f32)>::deserialize(deserializer)?;
+        Ok(Color::new(r, g, b, a))
+    }
+
+    pub fn default() -> Color {
+        WHITE
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ggez::graphics::WHITE;
@@ -70,4 +124,52 @@ mod test {
         assert_eq!(entity_data.color, color);
         assert!(matches!(entity_data.entity_type, EntityType::Platform));
     }
+
+    #[test]
+    fn ci_test_entity_data_from_ron() {
+        let entity_data: EntityData = ron::de::from_str(
+            "(x: 5.0, y: 20.0, width: 10.0, height: 15.0, color: (1.0, 0.0, 0.0, 1.0), entity_type: Platform)",
+        )
+        .unwrap();
+        assert_eq!(
+            entity_data,
+            EntityData::new(
+                5.0,
+                20.0,
+                10.0,
+                15.0,
+                Color::new(1.0, 0.0, 0.0, 1.0),
+                EntityType::Platform
+            )
+        );
+    }
+
+    #[test]
+    fn ci_test_entity_data_color_defaults_to_white() {
+        let entity_data: EntityData = serde_json::from_str(
+            r#"{"x": 5.0, "y": 20.0, "width": 10.0, "height": 15.0, "entity_type": "Platform"}"#,
+        )
+        .unwrap();
+        assert_eq!(entity_data.color, WHITE);
+    }
+
+    #[test]
+    fn ci_test_entity_data_with_sprite_and_z() {
+        let entity_data: EntityData = ron::de::from_str(
+            r#"(x: 5.0, y: 20.0, width: 10.0, height: 15.0, entity_type: Platform, sprite: Some("grass"), z: -1)"#,
+        )
+        .unwrap();
+        assert_eq!(entity_data.sprite.as_deref(), Some("grass"));
+        assert_eq!(entity_data.z, -1);
+    }
+
+    #[test]
+    fn ci_test_entity_data_layer_defaults_by_entity_type() {
+        let mut entity_data = EntityData::default();
+        assert_eq!(entity_data.layer(), RenderLayer::Tiles);
+        entity_data.entity_type = EntityType::Goal;
+        assert_eq!(entity_data.layer(), RenderLayer::Entities);
+        entity_data.layer = Some(RenderLayer::Foreground);
+        assert_eq!(entity_data.layer(), RenderLayer::Foreground);
+    }
 }
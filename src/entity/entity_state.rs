@@ -1,6 +1,12 @@
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum EntityState {
     None,
     Falling,
     Standing,
+    /// Moving up while the jump button is still pushing us higher
+    Jumping,
+    /// Moving up after the jump stopped being boosted
+    Rising,
 }
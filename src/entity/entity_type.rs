@@ -1,5 +1,27 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use super::render_layer::RenderLayer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum EntityType {
     Player,
     Platform,
+    /// Touching it finishes the level
+    Goal,
+    /// The player picks it up by touching it
+    Item,
+    /// Moved by the behavior chosen in the level
+    Enemy,
+}
+
+impl EntityType {
+    /// The layer used when the level does not choose one
+    pub fn default_layer(self) -> RenderLayer {
+        match self {
+            EntityType::Platform => RenderLayer::Tiles,
+            EntityType::Player | EntityType::Goal | EntityType::Item | EntityType::Enemy => {
+                RenderLayer::Entities
+            }
+        }
+    }
 }
@@ -1,28 +1,44 @@
+use ggez::graphics::Rect;
 use ggez::nalgebra::Vector2;
 use ggez::{Context, GameResult};
 
+use crate::ai_system::{AiSystem, Senses};
 use crate::draw_system::DrawSystem;
 use crate::drawables::Drawables;
+use crate::input::InputState;
 use crate::physics_system::PhysicsSystem;
 
 use self::entity_state::EntityState;
+use self::entity_type::EntityType;
+use self::render_layer::RenderLayer;
 
 pub mod builder;
 pub mod entity_data;
 pub mod entity_state;
 pub mod entity_type;
+pub mod render_layer;
 
 #[derive(Debug)]
 pub struct Entity {
     pub location: Vector2<f32>,
+    /// where the entity was before the last update, drawing happens between here and location
+    previous_location: Vector2<f32>,
     pub width: f32,
     pub height: f32,
     draw_system: Option<Box<dyn DrawSystem>>,
     affected_by_gravity: bool,
     pub physics_system: Option<Box<dyn PhysicsSystem>>,
+    /// steers the physics system of entities that the player does not control
+    ai_system: Option<Box<dyn AiSystem>>,
     pub collidable: bool,
     state: EntityState,
     pub id: u32,
+    player_controlled: bool,
+    pub entity_type: Option<EntityType>,
+    /// which layer the entity is drawn in, entities with a higher z are drawn on top within a layer
+    pub layer: RenderLayer,
+    /// entities with a higher z are drawn on top
+    pub z: i32,
 }
 
 impl Entity {
@@ -46,6 +62,8 @@ impl Entity {
     pub fn set_location(&mut self, x: f32, y: f32) -> &mut Self {
         self.location.x = x;
         self.location.y = y;
+        // moving this way is a jump, so it is not drawn sliding across from where it was
+        self.previous_location = self.location;
 
         self
     }
@@ -66,6 +84,38 @@ impl Entity {
         self
     }
 
+    pub fn set_ai_system(&mut self, ai_system: Box<dyn AiSystem>) -> &mut Self {
+        self.ai_system = Some(ai_system);
+        self
+    }
+
+    /// Let the AI system decide where to go this tick, call it before update
+    pub fn think(&mut self, player_location: Option<Vector2<f32>>, nearby: &[&Entity]) {
+        if let (Some(ai_system), Some(physics_system)) =
+            (&mut self.ai_system, &mut self.physics_system)
+        {
+            let senses = Senses {
+                location: self.location,
+                width: self.width,
+                height: self.height,
+                contacts: *physics_system.get_contacts(),
+                player_location,
+                nearby,
+            };
+            let velocity = ai_system.think(&senses);
+            physics_system.steer(&velocity);
+        }
+    }
+
+    pub fn set_player_controlled(&mut self, player_controlled: bool) -> &mut Self {
+        self.player_controlled = player_controlled;
+        self
+    }
+
+    pub fn is_player_controlled(&self) -> bool {
+        self.player_controlled
+    }
+
     pub fn set_collidable(&mut self, collidable: bool) -> &mut Self {
         self.collidable = collidable;
         self
@@ -85,14 +135,63 @@ impl Entity {
         self
     }
 
+    /// The axis aligned box around the entity. The location of an entity is its center.
+    /// ```
+    /// use game_template_platform::entity::Entity;
+    /// use ggez::graphics::Rect;
+    /// let mut player = Entity::new();
+    /// player.set_location(50.0, 60.0).set_size(10.0, 20.0);
+    /// assert_eq!(player.bounding_box(), Rect::new(45.0, 50.0, 10.0, 20.0));
+    /// ```
+    pub fn bounding_box(&self) -> Rect {
+        Rect::new(
+            self.location.x - self.width / 2.0,
+            self.location.y - self.height / 2.0,
+            self.width,
+            self.height,
+        )
+    }
+
+    pub fn get_state(&self) -> EntityState {
+        self.state
+    }
+
+    /// How far the entity moves each tick, entities without a physics system do not move
+    pub fn velocity(&self) -> Vector2<f32> {
+        match &self.physics_system {
+            Some(physics_system) => *physics_system.get_velocity(),
+            None => Vector2::new(0.0, 0.0),
+        }
+    }
+
+    /// Move the entity to the location and stop all of its movement
+    pub fn respawn(&mut self, x: f32, y: f32) {
+        self.set_location(x, y);
+        self.state = EntityState::None;
+        if let Some(physics_system) = &mut self.physics_system {
+            physics_system.reset();
+        }
+    }
+
+    /// Where to draw the entity, lag is how far we are between the last update and the next one from 0.0 to 1.0
+    /// ```
+    /// use game_template_platform::entity::Entity;
+    /// use ggez::nalgebra::Vector2;
+    /// let mut entity = Entity::new();
+    /// entity.set_location(10.0, 20.0);
+    /// assert_eq!(entity.interpolated_location(0.5), Vector2::new(10.0, 20.0));
+    /// ```
+    pub fn interpolated_location(&self, lag: f32) -> Vector2<f32> {
+        self.previous_location + (self.location - self.previous_location) * lag
+    }
+
     pub fn draw(&self, context: &mut Context, drawables: &Drawables, lag: f32) -> GameResult {
         if let Some(draw_system) = &self.draw_system {
             draw_system.draw(
                 drawables,
                 context,
-                &self.location,
+                &self.interpolated_location(lag),
                 (self.width, self.height),
-                lag,
                 &self.physics_system,
             )?;
         }
@@ -101,20 +200,25 @@ impl Entity {
     }
 
     /// Update the entity and return true if the location of the entity changed
-    pub fn update(&mut self, gravity: &Vector2<f32>, collidable_others: Vec<Entity>) -> bool {
+    pub fn update(
+        &mut self,
+        gravity: &Vector2<f32>,
+        collidable_others: &[&Entity],
+        input: &InputState,
+    ) -> bool {
         let original_location = self.location;
+        self.previous_location = self.location;
         if let Some(physics_system) = &mut self.physics_system {
-            // and we are not standing
+            if self.player_controlled {
+                physics_system.handle_input(input);
+            }
+
+            // gravity is applied even while standing, landing on the ground every tick is how the physics
+            // system knows that we are still standing and have not walked off of a ledge
             if self.affected_by_gravity {
-                match self.state {
-                    EntityState::None => physics_system.apply_force(gravity),
-                    EntityState::Falling => physics_system.apply_force(gravity),
-                    EntityState::Standing => {}
-                }
+                physics_system.apply_force(gravity);
             }
 
-            // we might also want to pass in the gravity force, and then the update function will determine if the gravity force should be applied
-            // We will also want to create and pass in an entity state here for updating.
             physics_system.update(
                 &mut self.location,
                 self.width,
@@ -123,7 +227,25 @@ impl Entity {
                 &mut self.state,
             );
         }
-        original_location == self.location
+        if let Some(draw_system) = &mut self.draw_system {
+            draw_system.update(self.state, &self.physics_system);
+        }
+        original_location != self.location
+    }
+
+    /// The sprite to draw this entity with in a sprite batch, if it has one
+    pub fn batched_sprite(&self) -> Option<&str> {
+        self.draw_system
+            .as_ref()
+            .and_then(|draw_system| draw_system.batched_sprite())
+    }
+
+    /// Animation events, such as footsteps, since the last time they were taken
+    pub fn take_animation_events(&mut self) -> Vec<String> {
+        match &mut self.draw_system {
+            Some(draw_system) => draw_system.take_events(),
+            None => vec![],
+        }
     }
 }
 
@@ -135,34 +257,25 @@ impl Default for Entity {
         let draw_system = None;
         let affected_by_gravity = false;
         let physics_system = None;
+        let ai_system = None;
         let collidable = false;
 
         Self {
             location,
+            previous_location: location,
             width,
             height,
             draw_system,
             affected_by_gravity,
             physics_system,
+            ai_system,
             collidable,
             state: EntityState::None,
             id: 0,
-        }
-    }
-}
-
-impl Clone for Entity {
-    fn clone(&self) -> Self {
-        Self {
-            location: self.location,
-            width: self.width,
-            height: self.height,
-            draw_system: None,
-            affected_by_gravity: self.affected_by_gravity,
-            physics_system: None,
-            collidable: self.collidable,
-            state: self.state,
-            id: self.id,
+            player_controlled: false,
+            entity_type: None,
+            layer: RenderLayer::default(),
+            z: 0,
         }
     }
 }
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// The layers are drawn in the order they are listed here, back to front
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
+)]
+pub enum RenderLayer {
+    /// behind the level, such as scenery that does not scroll with parallax
+    Background,
+    /// the static parts of the level, tiles with sprites are drawn together in one batch
+    Tiles,
+    /// the player and everything else that moves
+    #[default]
+    Entities,
+    /// in front of everything, such as leaves that the player walks behind
+    Foreground,
+}
+
+impl RenderLayer {
+    pub const ALL: [RenderLayer; 4] = [
+        RenderLayer::Background,
+        RenderLayer::Tiles,
+        RenderLayer::Entities,
+        RenderLayer::Foreground,
+    ];
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// What we know about a file to tell when it changed, None when the file does not exist
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// Notices when files are changed, created or deleted by checking their modified times whenever asked
+#[derive(Debug, Default)]
+pub struct FileWatcher {
+    files: Vec<(PathBuf, FileStamp)>,
+}
+
+impl FileWatcher {
+    pub fn new<P: AsRef<Path>>(paths: &[P]) -> Self {
+        let files = paths
+            .iter()
+            .map(|path| (path.as_ref().to_owned(), stamp(path.as_ref())))
+            .collect();
+        Self { files }
+    }
+
+    /// The files that changed since the last time we checked
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        for (path, last_stamp) in self.files.iter_mut() {
+            let current_stamp = stamp(path);
+            if current_stamp != *last_stamp {
+                *last_stamp = current_stamp;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn stamp(path: &Path) -> FileStamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn ci_test_file_watcher_sees_changes() {
+        let path = env::temp_dir().join(format!("file_watcher_test_{}.ron", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut file_watcher = FileWatcher::new(&[&path]);
+        assert!(file_watcher.changed().is_empty());
+
+        fs::write(&path, "()").unwrap();
+        assert_eq!(file_watcher.changed(), vec![path.clone()]);
+        assert!(file_watcher.changed().is_empty());
+
+        fs::write(&path, "(vsync: true)").unwrap();
+        assert_eq!(file_watcher.changed(), vec![path.clone()]);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(file_watcher.changed(), vec![path]);
+    }
+}
@@ -0,0 +1,198 @@
+pub mod file_watcher;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use eyre::Result;
+use ggez::graphics::{self, Color, DrawParam, Text};
+use ggez::{Context, GameResult};
+
+use crate::config::Config;
+use crate::level::Level;
+use crate::world::World;
+
+use self::file_watcher::FileWatcher;
+
+/// Checking the files is cheap but not free, so only do it this often
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+const ERROR_COLOR: Color = Color::new(1.0, 0.3, 0.3, 1.0);
+
+/// Applies changes to the config and level files to the running game. Files that fail to load leave the game as it
+/// was and show the error on screen until they are fixed.
+#[derive(Debug)]
+pub struct HotReload {
+    config_path: PathBuf,
+    config_watcher: FileWatcher,
+    level_watcher: FileWatcher,
+    next_check: Duration,
+    error: Option<String>,
+}
+
+impl HotReload {
+    pub fn new(config_path: PathBuf, config: &Config) -> Self {
+        Self {
+            config_watcher: FileWatcher::new(&[&config_path]),
+            level_watcher: FileWatcher::new(&config.level_paths),
+            config_path,
+            next_check: Duration::from_secs(0),
+            error: None,
+        }
+    }
+
+    /// Why the last reload failed, if it did
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Call every frame with the time since the game started. Reloading a level tells the world to change to it, so
+    /// the usual level changed event comes out of the world.
+    pub fn update(&mut self, now: Duration, config: &mut Config, world: &mut World) {
+        if now < self.next_check {
+            return;
+        }
+        self.next_check = now + CHECK_INTERVAL;
+        let config_changed = !self.config_watcher.changed().is_empty();
+        let levels_changed = !self.level_watcher.changed().is_empty();
+        if !config_changed && !levels_changed {
+            return;
+        }
+
+        match self.reload(config_changed, levels_changed, config, world) {
+            Ok(()) => self.error = None,
+            Err(error) => {
+                let error = format!("{:#}", error);
+                eprintln!("could not reload: {}", error);
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// Load the config and the levels before touching the world, so a mistake in any of them leaves the game as
+    /// it was. After a failed reload the config is read again with the next change, whichever file it is in.
+    fn reload(
+        &mut self,
+        config_changed: bool,
+        levels_changed: bool,
+        config: &mut Config,
+        world: &mut World,
+    ) -> Result<()> {
+        let new_config = if config_changed || self.error.is_some() {
+            let new_config = Config::load(&self.config_path)?;
+            if new_config.level_paths != config.level_paths {
+                // watch the new level files even if they fail to load, fixing them should reload them
+                self.level_watcher = FileWatcher::new(&new_config.level_paths);
+            }
+            Some(new_config)
+        } else {
+            None
+        };
+        let next_config = new_config.as_ref().unwrap_or(config);
+        let levels = if levels_changed || next_config.level_paths != config.level_paths {
+            let levels = next_config
+                .level_paths
+                .iter()
+                .map(|path| {
+                    Level::load(
+                        path,
+                        next_config.world_unit_width,
+                        next_config.world_unit_height,
+                    )
+                })
+                .collect::<Result<Vec<Level>>>()?;
+            Some(levels)
+        } else {
+            None
+        };
+
+        // the world undoes a level that fails to build, the config only goes in once the levels did
+        if let Some(levels) = levels {
+            world.replace_levels(levels)?;
+        }
+        if let Some(new_config) = new_config {
+            world.apply_config(&new_config)?;
+            *config = new_config;
+        }
+        Ok(())
+    }
+
+    /// Show why the last reload failed, in screen coordinates
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        match &self.error {
+            Some(error) => {
+                let text = Text::new(format!("reload failed: {}", error));
+                graphics::draw(
+                    context,
+                    &text,
+                    DrawParam::new().dest([10.0, 60.0]).color(ERROR_COLOR),
+                )
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_hot_reload_applies_the_config_and_keeps_errors() {
+        let config_path =
+            env::temp_dir().join(format!("hot_reload_test_{}.ron", std::process::id()));
+        fs::write(&config_path, "()").unwrap();
+        let mut config = Config::load(&config_path).unwrap();
+        let mut world = World::from_config(&config, (1280.0, 720.0)).unwrap();
+        let mut hot_reload = HotReload::new(config_path.clone(), &config);
+
+        fs::write(&config_path, "(gravity_force: 0.5, player_width: 20.0)").unwrap();
+        hot_reload.update(Duration::from_secs(1), &mut config, &mut world);
+        assert_eq!(hot_reload.error(), None);
+        assert_eq!(config.gravity_force, 0.5);
+        assert_eq!(world.player().unwrap().width, 20.0);
+
+        fs::write(&config_path, "(gravity_force: oops)").unwrap();
+        // too soon to check again
+        hot_reload.update(Duration::from_millis(1200), &mut config, &mut world);
+        assert_eq!(hot_reload.error(), None);
+        hot_reload.update(Duration::from_secs(2), &mut config, &mut world);
+        assert!(hot_reload
+            .error()
+            .unwrap()
+            .contains("error in config at line 1"));
+        assert_eq!(config.gravity_force, 0.5);
+
+        // a broken level keeps the whole reload from happening, fixing it finishes the reload
+        let level_path =
+            env::temp_dir().join(format!("hot_reload_test_level_{}.ron", std::process::id()));
+        fs::write(&level_path, "(width: oops)").unwrap();
+        fs::write(
+            &config_path,
+            format!(
+                "(gravity_force: 0.25, player_width: 30.0, level_paths: [{:?}])",
+                level_path
+            ),
+        )
+        .unwrap();
+        hot_reload.update(Duration::from_secs(3), &mut config, &mut world);
+        assert!(hot_reload
+            .error()
+            .unwrap()
+            .contains("could not load level file"));
+        assert_eq!(config.gravity_force, 0.5);
+        assert_eq!(world.player().unwrap().width, 20.0);
+
+        fs::write(&level_path, "(width: 500.0, height: 500.0, entities: [])").unwrap();
+        hot_reload.update(Duration::from_secs(4), &mut config, &mut world);
+        assert_eq!(hot_reload.error(), None);
+        assert_eq!(config.gravity_force, 0.25);
+        assert_eq!(world.player().unwrap().width, 30.0);
+        assert_eq!(world.current_level().unwrap().width, 500.0);
+
+        fs::remove_file(&config_path).unwrap();
+        fs::remove_file(&level_path).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+use std::str::FromStr;
+
+use eyre::{eyre, Report};
+use serde::{Deserialize, Serialize};
+
+/// The things a player can ask their character to do, independent of the device they used to ask
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Crouch,
+    /// Open or close the pause menu, or leave the title screen
+    Pause,
+    /// Choose the highlighted thing in a menu
+    Confirm,
+}
+
+impl FromStr for Action {
+    type Err = Report;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "MoveLeft" => Ok(Action::MoveLeft),
+            "MoveRight" => Ok(Action::MoveRight),
+            "Jump" => Ok(Action::Jump),
+            "Crouch" => Ok(Action::Crouch),
+            "Pause" => Ok(Action::Pause),
+            "Confirm" => Ok(Action::Confirm),
+            _ => Err(eyre!(
+                "{:?} is not an action, use one of: MoveLeft, MoveRight, Jump, Crouch, Pause, Confirm",
+                name
+            )),
+        }
+    }
+}
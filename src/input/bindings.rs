@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use ggez::event::{Button, KeyCode};
+
+use super::action::Action;
+
+/// Maps keyboard keys and gamepad buttons to actions. More than one key or button can be bound to the same action.
+#[derive(Debug, Clone)]
+pub struct InputBindings {
+    keys: HashMap<KeyCode, Action>,
+    buttons: HashMap<Button, Action>,
+    /// How far the left stick needs to be pushed before it counts as moving
+    pub stick_dead_zone: f32,
+}
+
+impl InputBindings {
+    /// Create bindings without anything bound
+    pub fn new() -> Self {
+        Self {
+            keys: HashMap::new(),
+            buttons: HashMap::new(),
+            stick_dead_zone: 0.3,
+        }
+    }
+
+    /// Bind the key to the action, replacing whatever the key was bound to before
+    pub fn bind_key(&mut self, key: KeyCode, action: Action) -> &mut Self {
+        self.keys.insert(key, action);
+        self
+    }
+
+    pub fn unbind_key(&mut self, key: KeyCode) -> &mut Self {
+        self.keys.remove(&key);
+        self
+    }
+
+    /// Bind the gamepad button to the action, replacing whatever the button was bound to before
+    pub fn bind_button(&mut self, button: Button, action: Action) -> &mut Self {
+        self.buttons.insert(button, action);
+        self
+    }
+
+    pub fn unbind_button(&mut self, button: Button) -> &mut Self {
+        self.buttons.remove(&button);
+        self
+    }
+
+    /// Remove every key and button bound to the action
+    pub fn unbind_action(&mut self, action: Action) -> &mut Self {
+        self.keys.retain(|_, bound_action| *bound_action != action);
+        self.buttons
+            .retain(|_, bound_action| *bound_action != action);
+        self
+    }
+
+    pub fn action_for_key(&self, key: KeyCode) -> Option<Action> {
+        self.keys.get(&key).copied()
+    }
+
+    pub fn action_for_button(&self, button: Button) -> Option<Action> {
+        self.buttons.get(&button).copied()
+    }
+
+    pub fn keys_for_action(&self, action: Action) -> Vec<KeyCode> {
+        self.keys
+            .iter()
+            .filter(|(_, bound_action)| **bound_action == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+}
+
+impl Default for InputBindings {
+    /// Arrow keys and WASD on the keyboard, the d-pad and south face button on a gamepad.
+    /// Escape or start pauses and enter or the east face button confirms.
+    fn default() -> Self {
+        let mut bindings = Self::new();
+        bindings
+            .bind_key(KeyCode::Left, Action::MoveLeft)
+            .bind_key(KeyCode::A, Action::MoveLeft)
+            .bind_key(KeyCode::Right, Action::MoveRight)
+            .bind_key(KeyCode::D, Action::MoveRight)
+            .bind_key(KeyCode::Up, Action::Jump)
+            .bind_key(KeyCode::W, Action::Jump)
+            .bind_key(KeyCode::Space, Action::Jump)
+            .bind_key(KeyCode::Down, Action::Crouch)
+            .bind_key(KeyCode::S, Action::Crouch)
+            .bind_key(KeyCode::Escape, Action::Pause)
+            .bind_key(KeyCode::Return, Action::Confirm)
+            .bind_button(Button::DPadLeft, Action::MoveLeft)
+            .bind_button(Button::DPadRight, Action::MoveRight)
+            .bind_button(Button::South, Action::Jump)
+            .bind_button(Button::DPadDown, Action::Crouch)
+            .bind_button(Button::Start, Action::Pause)
+            .bind_button(Button::East, Action::Confirm);
+        bindings
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ci_test_default_bindings() {
+        let bindings = InputBindings::default();
+        assert_eq!(bindings.action_for_key(KeyCode::A), Some(Action::MoveLeft));
+        assert_eq!(bindings.action_for_key(KeyCode::Space), Some(Action::Jump));
+        assert_eq!(
+            bindings.action_for_button(Button::South),
+            Some(Action::Jump)
+        );
+        assert_eq!(bindings.action_for_key(KeyCode::Q), None);
+    }
+
+    #[test]
+    fn ci_test_rebinding_a_key() {
+        let mut bindings = InputBindings::new();
+        bindings.bind_key(KeyCode::J, Action::Jump);
+        assert_eq!(bindings.action_for_key(KeyCode::J), Some(Action::Jump));
+        bindings.bind_key(KeyCode::J, Action::Crouch);
+        assert_eq!(bindings.action_for_key(KeyCode::J), Some(Action::Crouch));
+        bindings.unbind_key(KeyCode::J);
+        assert_eq!(bindings.action_for_key(KeyCode::J), None);
+    }
+
+    #[test]
+    fn ci_test_unbinding_an_action() {
+        let mut bindings = InputBindings::default();
+        bindings.unbind_action(Action::Jump);
+        assert!(bindings.keys_for_action(Action::Jump).is_empty());
+        assert_eq!(bindings.action_for_button(Button::South), None);
+        assert_eq!(bindings.keys_for_action(Action::MoveLeft).len(), 2);
+    }
+}
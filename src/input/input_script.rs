@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use eyre::{bail, eyre, Report, Result, WrapErr};
+
+use super::action::Action;
+use super::InputState;
+
+/// One press or release at the start of a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScriptedInput {
+    pub tick: u32,
+    pub action: Action,
+    pub pressed: bool,
+}
+
+/// Input written ahead of time for playing the game without anyone at the keyboard. Each line is the tick, press or
+/// release and the action, and lines starting with # are comments.
+/// ```
+/// use game_template_platform::input::input_script::InputScript;
+/// let script: InputScript = "
+///     0 press MoveRight
+///     30 press Jump
+///     40 release Jump
+/// ".parse().unwrap();
+/// assert_eq!(script.inputs().len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputScript {
+    inputs: Vec<ScriptedInput>,
+}
+
+impl InputScript {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read input script {}", path.display()))?
+            .parse()
+            .wrap_err_with(|| format!("could not load input script {}", path.display()))
+    }
+
+    /// The inputs sorted by tick, inputs on the same tick stay in the order they were written
+    pub fn inputs(&self) -> &[ScriptedInput] {
+        &self.inputs
+    }
+
+    /// Press and release the actions scripted for the tick
+    pub fn apply(&self, tick: u32, input: &mut InputState) {
+        self.inputs
+            .iter()
+            .filter(|scripted| scripted.tick == tick)
+            .for_each(|scripted| {
+                if scripted.pressed {
+                    input.press(scripted.action);
+                } else {
+                    input.release(scripted.action);
+                }
+            });
+    }
+}
+
+impl FromStr for InputScript {
+    type Err = Report;
+
+    fn from_str(script: &str) -> Result<Self> {
+        let mut inputs = vec![];
+        for (index, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let scripted =
+                parse_line(line).wrap_err_with(|| format!("line {}: {:?}", index + 1, line))?;
+            inputs.push(scripted);
+        }
+        inputs.sort_by_key(|scripted| scripted.tick);
+        Ok(Self { inputs })
+    }
+}
+
+fn parse_line(line: &str) -> Result<ScriptedInput> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() != 3 {
+        bail!("expected a tick, press or release and an action");
+    }
+    let tick = parts[0]
+        .parse()
+        .map_err(|_| eyre!("{:?} is not a tick number", parts[0]))?;
+    let pressed = match parts[1] {
+        "press" => true,
+        "release" => false,
+        other => bail!("{:?} should be press or release", other),
+    };
+    let action = parts[2].parse()?;
+    Ok(ScriptedInput {
+        tick,
+        action,
+        pressed,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ci_test_apply_input_script() {
+        let script: InputScript = "10 release MoveRight\n0 press MoveRight\n10 press Jump"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            script.inputs()[0],
+            ScriptedInput {
+                tick: 0,
+                action: Action::MoveRight,
+                pressed: true
+            }
+        );
+
+        let mut input = InputState::new();
+        script.apply(0, &mut input);
+        assert!(input.is_held(Action::MoveRight));
+        script.apply(5, &mut input);
+        assert!(input.is_held(Action::MoveRight));
+        script.apply(10, &mut input);
+        assert!(!input.is_held(Action::MoveRight));
+        assert!(input.was_pressed(Action::Jump));
+    }
+
+    #[test]
+    fn ci_test_input_script_errors_have_the_line() {
+        let error = "# comment\n0 press Fly".parse::<InputScript>().unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            r#"line 2: "0 press Fly": "Fly" is not an action, use one of: MoveLeft, MoveRight, Jump, Crouch, Pause, Confirm"#
+        );
+    }
+}
@@ -0,0 +1,287 @@
+pub mod action;
+pub mod bindings;
+pub mod input_script;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ggez::event::{Axis, Button, KeyCode};
+use ggez::nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use self::action::Action;
+use self::bindings::InputBindings;
+
+/// The actions being held down and the ones that started since the last update tick
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputState {
+    /// what is holding each action down, an action is held until all of them let go
+    #[serde(with = "held_format")]
+    held: BTreeMap<Action, BTreeSet<Source>>,
+    pressed: BTreeSet<Action>,
+    /// where the mouse is in the world, if it is over the window
+    #[serde(with = "mouse_location_format", default)]
+    mouse_location: Option<Vector2<f32>>,
+}
+
+/// Where a held action came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Source {
+    Key(KeyCode),
+    /// gamepad buttons are not ordered, so they are kept by their number
+    Button(u16),
+    LeftStick,
+    /// pressed through the input state itself, such as by an input script or a replay
+    Direct,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn press(&mut self, action: Action) {
+        self.press_from(action, Source::Direct);
+    }
+
+    pub fn release(&mut self, action: Action) {
+        self.release_from(action, Source::Direct);
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held.contains_key(&action)
+    }
+
+    fn press_from(&mut self, action: Action, source: Source) {
+        let sources = self.held.entry(action).or_default();
+        if sources.is_empty() {
+            self.pressed.insert(action);
+        }
+        sources.insert(source);
+    }
+
+    fn release_from(&mut self, action: Action, source: Source) {
+        if let Some(sources) = self.held.get_mut(&action) {
+            sources.remove(&source);
+            if sources.is_empty() {
+                self.held.remove(&action);
+            }
+        }
+    }
+
+    fn release_source(&mut self, source: Source) {
+        self.held.retain(|_, sources| {
+            sources.remove(&source);
+            !sources.is_empty()
+        });
+    }
+
+    /// True if the action started since the last update tick
+    pub fn was_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn set_mouse_location(&mut self, location: Option<Vector2<f32>>) {
+        self.mouse_location = location;
+    }
+
+    pub fn mouse_location(&self) -> Option<Vector2<f32>> {
+        self.mouse_location
+    }
+
+    /// Call after every fixed update so that a press is only seen by one tick
+    pub fn end_tick(&mut self) {
+        self.pressed.clear();
+    }
+
+    pub fn key_down(&mut self, bindings: &InputBindings, key: KeyCode) {
+        if let Some(action) = bindings.action_for_key(key) {
+            self.press_from(action, Source::Key(key));
+        }
+    }
+
+    /// Lets go of whatever the key was holding, even if the key was bound to something else since it went down
+    pub fn key_up(&mut self, key: KeyCode) {
+        self.release_source(Source::Key(key));
+    }
+
+    pub fn button_down(&mut self, bindings: &InputBindings, button: Button) {
+        if let Some(action) = bindings.action_for_button(button) {
+            self.press_from(action, Source::Button(button as u16));
+        }
+    }
+
+    /// Lets go of whatever the button was holding, even if the button was bound to something else since it went
+    /// down
+    pub fn button_up(&mut self, button: Button) {
+        self.release_source(Source::Button(button as u16));
+    }
+
+    /// The left stick moves left and right once it is pushed past the dead zone. Letting go of the stick only
+    /// lets go of what the stick was holding, not of keys or buttons for the same action.
+    pub fn axis_moved(&mut self, bindings: &InputBindings, axis: Axis, value: f32) {
+        if axis != Axis::LeftStickX {
+            return;
+        }
+
+        if value < -bindings.stick_dead_zone {
+            self.release_from(Action::MoveRight, Source::LeftStick);
+            self.press_from(Action::MoveLeft, Source::LeftStick);
+        } else if value > bindings.stick_dead_zone {
+            self.release_from(Action::MoveLeft, Source::LeftStick);
+            self.press_from(Action::MoveRight, Source::LeftStick);
+        } else {
+            self.release_from(Action::MoveLeft, Source::LeftStick);
+            self.release_from(Action::MoveRight, Source::LeftStick);
+        }
+    }
+}
+
+/// Only the held actions are saved, where they came from does not matter to a replay
+mod held_format {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Action, Source};
+
+    pub fn serialize<S: Serializer>(
+        held: &BTreeMap<Action, BTreeSet<Source>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        held.keys()
+            .collect::<BTreeSet<&Action>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Action, BTreeSet<Source>>, D::Error> {
+        let actions = BTreeSet::<Action>::deserialize(deserializer)?;
+        Ok(actions
+            .into_iter()
+            .map(|action| (action, BTreeSet::from([Source::Direct])))
+            .collect())
+    }
+}
+
+/// The mouse location is stored as an optional (x, y) tuple
+mod mouse_location_format {
+    use ggez::nalgebra::Vector2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        location: &Option<Vector2<f32>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        location
+            .map(|location| (location.x, location.y))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vector2<f32>>, D::Error> {
+        let location = Option::<(f32, f32)>::deserialize(deserializer)?;
+        Ok(location.map(|(x, y)| Vector2::new(x, y)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ci_test_pressing_and_releasing_keys() {
+        let bindings = InputBindings::default();
+        let mut input = InputState::new();
+        input.key_down(&bindings, KeyCode::D);
+        assert!(input.is_held(Action::MoveRight));
+        assert!(input.was_pressed(Action::MoveRight));
+
+        input.end_tick();
+        assert!(input.is_held(Action::MoveRight));
+        assert!(!input.was_pressed(Action::MoveRight));
+
+        input.key_up(KeyCode::D);
+        assert!(!input.is_held(Action::MoveRight));
+    }
+
+    #[test]
+    fn ci_test_key_repeat_is_not_a_new_press() {
+        let bindings = InputBindings::default();
+        let mut input = InputState::new();
+        input.key_down(&bindings, KeyCode::Space);
+        input.end_tick();
+        input.key_down(&bindings, KeyCode::Space);
+        assert!(!input.was_pressed(Action::Jump));
+    }
+
+    #[test]
+    fn ci_test_unbound_keys_are_ignored() {
+        let bindings = InputBindings::new();
+        let mut input = InputState::new();
+        input.key_down(&bindings, KeyCode::D);
+        assert_eq!(input, InputState::new());
+    }
+
+    #[test]
+    fn ci_test_gamepad_buttons_and_stick() {
+        let bindings = InputBindings::default();
+        let mut input = InputState::new();
+        input.button_down(&bindings, Button::South);
+        assert!(input.is_held(Action::Jump));
+        input.button_up(Button::South);
+        assert!(!input.is_held(Action::Jump));
+
+        input.axis_moved(&bindings, Axis::LeftStickX, -0.8);
+        assert!(input.is_held(Action::MoveLeft));
+        input.axis_moved(&bindings, Axis::LeftStickX, 0.9);
+        assert!(input.is_held(Action::MoveRight));
+        assert!(!input.is_held(Action::MoveLeft));
+        input.axis_moved(&bindings, Axis::LeftStickX, 0.1);
+        assert!(!input.is_held(Action::MoveRight));
+    }
+
+    #[test]
+    fn ci_test_two_keys_for_one_action() {
+        let bindings = InputBindings::default();
+        let mut input = InputState::new();
+        input.key_down(&bindings, KeyCode::Left);
+        input.key_down(&bindings, KeyCode::A);
+        input.key_up(KeyCode::Left);
+        assert!(input.is_held(Action::MoveLeft));
+        input.key_up(KeyCode::A);
+        assert!(!input.is_held(Action::MoveLeft));
+    }
+
+    #[test]
+    fn ci_test_a_neutral_stick_does_not_release_the_keyboard() {
+        let bindings = InputBindings::default();
+        let mut input = InputState::new();
+        input.key_down(&bindings, KeyCode::Right);
+        input.axis_moved(&bindings, Axis::LeftStickX, 0.05);
+        assert!(input.is_held(Action::MoveRight));
+
+        input.axis_moved(&bindings, Axis::LeftStickX, 0.9);
+        input.key_up(KeyCode::Right);
+        assert!(input.is_held(Action::MoveRight));
+        input.axis_moved(&bindings, Axis::LeftStickX, 0.0);
+        assert!(!input.is_held(Action::MoveRight));
+    }
+
+    #[test]
+    fn ci_test_rebinding_a_held_key_still_lets_go_of_the_old_action() {
+        let mut bindings = InputBindings::default();
+        let mut input = InputState::new();
+        input.key_down(&bindings, KeyCode::D);
+        bindings.bind_key(KeyCode::D, Action::Jump);
+        input.key_up(KeyCode::D);
+        assert!(!input.is_held(Action::MoveRight));
+        assert!(!input.is_held(Action::Jump));
+
+        input.button_down(&bindings, Button::South);
+        bindings.unbind_button(Button::South);
+        input.button_up(Button::South);
+        assert!(!input.is_held(Action::Jump));
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// A kind of item that can be picked up in a level, such as a coin. Items in the level name their kind.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemKind {
+    pub name: String,
+    /// what picking it up is worth, for example to the score
+    #[serde(default)]
+    pub value: i32,
+    /// used by items of this kind that do not have a sprite of their own
+    #[serde(default)]
+    pub sprite: Option<String>,
+}
+
+impl ItemKind {
+    pub fn new(name: &str, value: i32) -> Self {
+        Self {
+            name: name.to_owned(),
+            value,
+            sprite: None,
+        }
+    }
+}
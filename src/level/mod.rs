@@ -1,10 +1,55 @@
+pub mod item_kind;
+pub mod parallax_layer;
+pub mod spawn_point;
+pub mod tiled;
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use eyre::{eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
 use crate::entity::entity_data::EntityData;
 
-#[derive(Debug, Clone)]
+use self::item_kind::ItemKind;
+use self::parallax_layer::ParallaxLayer;
+use self::spawn_point::SpawnPoint;
+
+pub const PLAYER_SPAWN_POINT: &str = "player";
+
+/// A level can be written by hand in RON or JSON, for example
+/// ```ron
+/// (
+///     width: 5000.0,
+///     height: 5000.0,
+///     spawn_points: [(name: "player", x: 50.0, y: 50.0)],
+///     entities: [
+///         (x: 50.0, y: 500.0, width: 50.0, height: 50.0, color: (0.0, 1.0, 0.0, 1.0), entity_type: Platform),
+///         (x: 150.0, y: 475.0, width: 50.0, height: 25.0, entity_type: Platform, layer: Some(Foreground)),
+///         (x: 250.0, y: 450.0, width: 20.0, height: 20.0, entity_type: Item, item_kind: Some("coin")),
+///         (x: 350.0, y: 450.0, width: 40.0, height: 40.0, entity_type: Enemy, behavior: Some(WalkToLedge(speed: 1.0))),
+///     ],
+///     item_kinds: [(name: "coin", value: 10, sprite: Some("coin"))],
+///     parallax_layers: [
+///         (sprite: "hills", width: 1280.0, height: 360.0, y: 200.0, scroll_factor: (0.5, 0.2), repeat_x: true),
+///     ],
+/// )
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Level {
     pub width: f32,
     pub height: f32,
+    #[serde(rename = "entities")]
     pub entity_datas: Vec<EntityData>,
+    #[serde(default)]
+    pub spawn_points: Vec<SpawnPoint>,
+    /// images drawn behind the level that scroll slower than it
+    #[serde(default)]
+    pub parallax_layers: Vec<ParallaxLayer>,
+    /// the kinds of items that the items in this level can be
+    #[serde(default)]
+    pub item_kinds: Vec<ItemKind>,
 }
 
 impl Level {
@@ -13,12 +58,102 @@ impl Level {
             width,
             height,
             entity_datas,
+            spawn_points: vec![],
+            parallax_layers: vec![],
+            item_kinds: vec![],
+        }
+    }
+
+    pub fn add_spawn_point(&mut self, spawn_point: SpawnPoint) -> &mut Self {
+        self.spawn_points.push(spawn_point);
+        self
+    }
+
+    pub fn get_spawn_point(&self, name: &str) -> Option<&SpawnPoint> {
+        self.spawn_points
+            .iter()
+            .find(|spawn_point| spawn_point.name == name)
+    }
+
+    pub fn player_spawn_point(&self) -> Option<&SpawnPoint> {
+        self.get_spawn_point(PLAYER_SPAWN_POINT)
+    }
+
+    pub fn get_item_kind(&self, name: &str) -> Option<&ItemKind> {
+        self.item_kinds
+            .iter()
+            .find(|item_kind| item_kind.name == name)
+    }
+
+    /// Load a level file, the format is chosen by the extension which can be `ron` or `json`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Level> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read level file {}", path.display()))?;
+        let level = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ron") => Self::from_ron_str(&contents),
+            Some("json") => Self::from_json_str(&contents),
+            _ => Err(eyre!("level files need to end in .ron or .json")),
+        };
+
+        level.wrap_err_with(|| format!("could not load level file {}", path.display()))
+    }
+
+    /// Load a level made by hand or a map from the Tiled editor, Tiled maps are scaled so one tile is one world unit
+    pub fn load<P: AsRef<Path>>(path: P, unit_width: f32, unit_height: f32) -> Result<Level> {
+        let path = path.as_ref();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("tmx") | Some("tmj") => tiled::import(path, unit_width, unit_height),
+            _ => Self::from_path(path),
+        }
+    }
+
+    pub fn from_ron_str(ron: &str) -> Result<Level> {
+        ron::de::from_str(ron).map_err(|error| {
+            eyre!(
+                "error in level at line {}, column {}: {}",
+                error.position.line,
+                error.position.col,
+                error.code
+            )
+        })
+    }
+
+    pub fn from_json_str(json: &str) -> Result<Level> {
+        serde_json::from_str(json).map_err(|error| {
+            // serde json puts the position at the end of the message, we want it at the front like ron errors
+            let message = error.to_string();
+            let message = message.split(" at line ").next().unwrap_or_default();
+            eyre!(
+                "error in level at line {}, column {}: {}",
+                error.line(),
+                error.column(),
+                message
+            )
+        })
+    }
+}
+
+impl FromStr for Level {
+    type Err = eyre::Report;
+
+    /// Parse a level from JSON if it looks like a JSON object, otherwise from RON
+    fn from_str(level: &str) -> Result<Self> {
+        if level.trim_start().starts_with('{') {
+            Self::from_json_str(level)
+        } else {
+            Self::from_ron_str(level)
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use ggez::graphics::{Color, WHITE};
+
+    use crate::entity::entity_type::EntityType;
+    use crate::entity::render_layer::RenderLayer;
+
     use super::*;
 
     #[test]
@@ -29,4 +164,110 @@ mod test {
         assert_eq!(level.width, 10.0);
         assert_eq!(level.height, 11.0);
     }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_level_from_ron() {
+        let level: Level = r#"
+            (
+                width: 1000.0,
+                height: 500.0,
+                spawn_points: [(name: "player", x: 50.0, y: 60.0)],
+                entities: [
+                    (x: 25.0, y: 475.0, width: 50.0, height: 50.0, color: (1.0, 0.0, 0.0, 1.0), entity_type: Platform),
+                    (x: 75.0, y: 475.0, width: 50.0, height: 50.0, entity_type: Platform, layer: Some(Background), z: 1),
+                    (x: 125.0, y: 425.0, width: 20.0, height: 20.0, entity_type: Item, item_kind: Some("coin")),
+                ],
+                item_kinds: [(name: "coin", value: 10)],
+                parallax_layers: [
+                    (sprite: "hills", width: 1000.0, height: 250.0, scroll_factor: (0.5, 0.0), repeat_x: true),
+                ],
+            )
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(level.width, 1000.0);
+        assert_eq!(level.height, 500.0);
+        assert_eq!(level.entity_datas.len(), 3);
+        assert_eq!(level.entity_datas[0].color, Color::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(level.entity_datas[1].color, WHITE);
+        assert_eq!(level.entity_datas[1].layer(), RenderLayer::Background);
+        assert_eq!(level.entity_datas[2].item_kind.as_deref(), Some("coin"));
+        assert_eq!(
+            level.get_item_kind("coin"),
+            Some(&ItemKind::new("coin", 10))
+        );
+        assert!(level.get_item_kind("gem").is_none());
+        assert_eq!(level.parallax_layers[0].sprite, "hills");
+        assert_eq!(level.parallax_layers[0].scroll_factor, (0.5, 0.0));
+        assert_eq!(
+            level.player_spawn_point(),
+            Some(&SpawnPoint::new("player", 50.0, 60.0))
+        );
+    }
+
+    #[test]
+    fn ci_test_level_from_json() {
+        let level: Level = r#"{
+            "width": 1000.0,
+            "height": 500.0,
+            "entities": [
+                {"x": 25.0, "y": 475.0, "width": 50.0, "height": 50.0, "entity_type": "Platform"}
+            ]
+        }"#
+        .parse()
+        .unwrap();
+
+        assert_eq!(level.entity_datas[0].entity_type, EntityType::Platform);
+        assert!(level.spawn_points.is_empty());
+        assert!(level.player_spawn_point().is_none());
+    }
+
+    #[test]
+    fn ci_test_ron_errors_have_line_and_column() {
+        let error = Level::from_ron_str("(\n    width: 1000.0,\n    height: oops,\n)").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("error in level at line 3, column"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn ci_test_json_errors_have_line_and_column() {
+        let error =
+            Level::from_json_str("{\n  \"width\": 1000.0,\n  \"height\": [\n}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "error in level at line 3, column 12: invalid type: sequence, expected f32"
+        );
+    }
+
+    #[test]
+    fn ci_test_level_from_path() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/levels/level_01.ron");
+        let level = Level::from_path(path).unwrap();
+        assert!(!level.entity_datas.is_empty());
+        assert!(level.player_spawn_point().is_some());
+    }
+
+    #[test]
+    fn ci_test_level_from_path_with_unknown_extension() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let error = Level::from_path(path).unwrap_err();
+        assert!(format!("{:#}", error).contains("level files need to end in .ron or .json"));
+    }
+
+    #[test]
+    fn ci_test_level_round_trip() {
+        let mut level = Level::new(100.0, 100.0, vec![EntityData::default()]);
+        level.add_spawn_point(SpawnPoint::new("player", 1.0, 2.0));
+        let ron = ron::ser::to_string(&level).unwrap();
+        let loaded = Level::from_ron_str(&ron).unwrap();
+        assert_eq!(loaded.entity_datas, level.entity_datas);
+        assert_eq!(loaded.spawn_points, level.spawn_points);
+    }
 }
@@ -0,0 +1,86 @@
+use ggez::graphics::Rect;
+use ggez::nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// A background image that scrolls slower than the level to look further away
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParallaxLayer {
+    pub sprite: String,
+    /// the size of the image in the world
+    pub width: f32,
+    pub height: f32,
+    /// where the top left of the image is when the camera is centered on the top left corner of the level
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    /// how fast the layer moves compared to the level, 0.0 stays still on the screen and 1.0 moves with the level
+    pub scroll_factor: (f32, f32),
+    /// repeat the image side to side so that it covers the whole width of the screen
+    #[serde(default)]
+    pub repeat_x: bool,
+    /// layers with a higher z are drawn on top of the other layers
+    #[serde(default)]
+    pub z: i32,
+}
+
+impl ParallaxLayer {
+    /// The top left corners of the copies of the image to draw, in the world, for a camera centered at
+    /// `camera_location` that shows `view`
+    pub fn positions(&self, camera_location: Vector2<f32>, view: Rect) -> Vec<Vector2<f32>> {
+        let x = self.x + camera_location.x * (1.0 - self.scroll_factor.0);
+        let y = self.y + camera_location.y * (1.0 - self.scroll_factor.1);
+        if !self.repeat_x || self.width <= 0.0 {
+            return vec![Vector2::new(x, y)];
+        }
+
+        let first_copy = ((view.left() - x) / self.width).floor();
+        let last_copy = ((view.right() - x) / self.width).ceil();
+        (first_copy as i32..last_copy as i32)
+            .map(|copy| Vector2::new(x + copy as f32 * self.width, y))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mountains() -> ParallaxLayer {
+        ParallaxLayer {
+            sprite: "mountains".to_owned(),
+            width: 100.0,
+            height: 50.0,
+            x: 0.0,
+            y: 10.0,
+            scroll_factor: (0.5, 0.0),
+            repeat_x: false,
+            z: 0,
+        }
+    }
+
+    #[test]
+    fn ci_test_parallax_layer_scrolls_slower_than_the_camera() {
+        let layer = mountains();
+        let view = Rect::new(150.0, 0.0, 100.0, 100.0);
+        assert_eq!(
+            layer.positions(Vector2::new(200.0, 50.0), view),
+            vec![Vector2::new(100.0, 60.0)]
+        );
+    }
+
+    #[test]
+    fn ci_test_repeated_parallax_layer_covers_the_view() {
+        let layer = ParallaxLayer {
+            repeat_x: true,
+            ..mountains()
+        };
+        let view = Rect::new(150.0, 0.0, 250.0, 100.0);
+        let xs: Vec<f32> = layer
+            .positions(Vector2::new(200.0, 50.0), view)
+            .iter()
+            .map(|position| position.x)
+            .collect();
+        assert_eq!(xs, vec![100.0, 200.0, 300.0]);
+    }
+}
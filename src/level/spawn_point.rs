@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// A named location in a level where something starts, the player starts at the spawn point named "player"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl SpawnPoint {
+    pub fn new(name: &str, x: f32, y: f32) -> Self {
+        Self {
+            name: name.to_owned(),
+            x,
+            y,
+        }
+    }
+}
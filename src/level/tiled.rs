@@ -0,0 +1,644 @@
+use std::fs;
+use std::path::Path;
+
+use eyre::{bail, eyre, Result, WrapErr};
+use ggez::graphics::WHITE;
+use roxmltree::{Document, Node};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::entity::entity_data::EntityData;
+use crate::entity::entity_type::EntityType;
+use crate::entity::render_layer::RenderLayer;
+
+use super::spawn_point::SpawnPoint;
+use super::{Level, PLAYER_SPAWN_POINT};
+
+/// Tiled stores whether a tile is flipped in the highest bits of the tile id
+const TILE_FLIP_FLAGS: u32 = 0xE000_0000;
+const SUPPORTED_OBJECT_TYPES: &str = "platform, goal, spawn, player";
+
+/// Import a Tiled map saved as `.tmx` or `.tmj`. Every tile becomes a platform that is one world unit in size,
+/// and objects are scaled by the same amount so that they stay lined up with the tiles.
+pub fn import<P: AsRef<Path>>(path: P, unit_width: f32, unit_height: f32) -> Result<Level> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)
+        .wrap_err_with(|| format!("could not read tiled map {}", path.display()))?;
+    let map = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("tmx") => parse_tmx(&contents),
+        Some("tmj") => parse_tmj(&contents),
+        _ => Err(eyre!("tiled maps need to end in .tmx or .tmj")),
+    };
+
+    map.and_then(|map| map.into_level(unit_width, unit_height))
+        .wrap_err_with(|| format!("could not import tiled map {}", path.display()))
+}
+
+pub fn import_tmx_str(tmx: &str, unit_width: f32, unit_height: f32) -> Result<Level> {
+    parse_tmx(tmx)?.into_level(unit_width, unit_height)
+}
+
+pub fn import_tmj_str(tmj: &str, unit_width: f32, unit_height: f32) -> Result<Level> {
+    parse_tmj(tmj)?.into_level(unit_width, unit_height)
+}
+
+/// The parts of a Tiled map that we can turn into a level, no matter which file format it came from
+struct TiledMap {
+    width: u32,
+    height: u32,
+    tile_width: f32,
+    tile_height: f32,
+    layers: Vec<TiledLayer>,
+}
+
+enum TiledLayer {
+    Tiles {
+        width: u32,
+        data: Vec<u32>,
+        layer: RenderLayer,
+    },
+    Objects(Vec<TiledObject>),
+}
+
+struct TiledObject {
+    id: u32,
+    name: String,
+    object_type: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl TiledMap {
+    fn into_level(self, unit_width: f32, unit_height: f32) -> Result<Level> {
+        let scale_x = unit_width / self.tile_width;
+        let scale_y = unit_height / self.tile_height;
+        let mut level = Level::new(
+            self.width as f32 * unit_width,
+            self.height as f32 * unit_height,
+            vec![],
+        );
+
+        for layer in self.layers {
+            match layer {
+                TiledLayer::Tiles { width, data, layer } => {
+                    let tiles = data
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, gid)| *gid & !TILE_FLIP_FLAGS != 0);
+                    for (index, _) in tiles {
+                        let column = index as u32 % width;
+                        let row = index as u32 / width;
+                        let mut entity_data = EntityData::new(
+                            column as f32 * unit_width + unit_width / 2.0,
+                            row as f32 * unit_height + unit_height / 2.0,
+                            unit_width,
+                            unit_height,
+                            WHITE,
+                            EntityType::Platform,
+                        );
+                        entity_data.layer = Some(layer);
+                        level.entity_datas.push(entity_data);
+                    }
+                }
+                TiledLayer::Objects(objects) => {
+                    for object in objects {
+                        let width = object.width * scale_x;
+                        let height = object.height * scale_y;
+                        // tiled positions objects by their top left corner and we use the center
+                        let x = object.x * scale_x + width / 2.0;
+                        let y = object.y * scale_y + height / 2.0;
+                        match object.object_type.to_lowercase().as_str() {
+                            "platform" => level.entity_datas.push(EntityData::new(
+                                x,
+                                y,
+                                width,
+                                height,
+                                WHITE,
+                                EntityType::Platform,
+                            )),
+                            "goal" => level.entity_datas.push(EntityData::new(
+                                x,
+                                y,
+                                width,
+                                height,
+                                WHITE,
+                                EntityType::Goal,
+                            )),
+                            "player" => {
+                                level.add_spawn_point(SpawnPoint::new(PLAYER_SPAWN_POINT, x, y));
+                            }
+                            "spawn" => {
+                                let name = if object.name.is_empty() {
+                                    PLAYER_SPAWN_POINT
+                                } else {
+                                    &object.name
+                                };
+                                level.add_spawn_point(SpawnPoint::new(name, x, y));
+                            }
+                            "" => bail!(
+                                "tiled object {} {:?} needs a type, use one of: {}",
+                                object.id,
+                                object.name,
+                                SUPPORTED_OBJECT_TYPES
+                            ),
+                            _ => bail!(
+                                "tiled object {} {:?} has the type {:?} which is not supported, use one of: {}",
+                                object.id,
+                                object.name,
+                                object.object_type,
+                                SUPPORTED_OBJECT_TYPES
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(level)
+    }
+}
+
+fn parse_tmx(tmx: &str) -> Result<TiledMap> {
+    let document = Document::parse(tmx).map_err(|error| eyre!("invalid tmx file: {}", error))?;
+    let map = document.root_element();
+    if !map.has_tag_name("map") {
+        bail!("tmx files need to start with a map element");
+    }
+    let position = |node: Node| {
+        let position = document.text_pos_at(node.range().start);
+        format!("line {}, column {}", position.row, position.col)
+    };
+
+    if map.attribute("infinite") == Some("1") {
+        bail!("infinite tiled maps are not supported");
+    }
+
+    let mut layers = vec![];
+    for layer in map.children().filter(|node| node.is_element()) {
+        match layer.tag_name().name() {
+            "layer" => {
+                let width = tmx_attribute(layer, "width")?;
+                let data = layer
+                    .children()
+                    .find(|node| node.has_tag_name("data"))
+                    .ok_or_else(|| eyre!("tile layer at {} has no data", position(layer)))?;
+                let data = match data.attribute("encoding") {
+                    Some("csv") => data
+                        .text()
+                        .unwrap_or_default()
+                        .split(',')
+                        .map(|gid| gid.trim())
+                        .filter(|gid| !gid.is_empty())
+                        .map(|gid| {
+                            gid.parse::<u32>().map_err(|_| {
+                                eyre!("tile id {:?} at {} is not a number", gid, position(data))
+                            })
+                        })
+                        .collect::<Result<Vec<u32>>>()?,
+                    None => data
+                        .children()
+                        .filter(|node| node.has_tag_name("tile"))
+                        .map(|tile| Ok(tmx_attribute(tile, "gid").unwrap_or(0)))
+                        .collect::<Result<Vec<u32>>>()?,
+                    Some(encoding) => bail!(
+                        "tile layer at {} uses {} encoding, save the map with CSV tile layer format",
+                        position(layer),
+                        encoding
+                    ),
+                };
+                let name = layer.attribute("name").unwrap_or_default();
+                check_tile_layer(name, width, tmx_attribute(layer, "height")?, data.len())?;
+                let layer = tile_render_layer(name);
+                layers.push(TiledLayer::Tiles { width, data, layer });
+            }
+            "objectgroup" => {
+                let mut objects = vec![];
+                for object in layer.children().filter(|node| node.has_tag_name("object")) {
+                    if object.attribute("gid").is_some() {
+                        bail!("tile object at {} is not supported", position(object));
+                    }
+                    let shape = object.children().find(|node| {
+                        ["ellipse", "polygon", "polyline", "text"].contains(&node.tag_name().name())
+                    });
+                    if let Some(shape) = shape {
+                        bail!(
+                            "{} object at {} is not supported, only rectangles and points are",
+                            shape.tag_name().name(),
+                            position(object)
+                        );
+                    }
+                    let object_type = object
+                        .attribute("type")
+                        .or_else(|| object.attribute("class"))
+                        .or_else(|| tmx_type_property(object))
+                        .unwrap_or_default();
+                    objects.push(TiledObject {
+                        id: tmx_attribute(object, "id")?,
+                        name: object.attribute("name").unwrap_or_default().to_owned(),
+                        object_type: object_type.to_owned(),
+                        x: tmx_attribute(object, "x")?,
+                        y: tmx_attribute(object, "y")?,
+                        width: tmx_attribute(object, "width").unwrap_or(0.0),
+                        height: tmx_attribute(object, "height").unwrap_or(0.0),
+                    });
+                }
+                layers.push(TiledLayer::Objects(objects));
+            }
+            "imagelayer" | "group" => bail!(
+                "{} at {} is not supported, only tile layers and object layers are",
+                layer.tag_name().name(),
+                position(layer)
+            ),
+            _ => {}
+        }
+    }
+
+    Ok(TiledMap {
+        width: tmx_attribute(map, "width")?,
+        height: tmx_attribute(map, "height")?,
+        tile_width: tmx_attribute(map, "tilewidth")?,
+        tile_height: tmx_attribute(map, "tileheight")?,
+        layers,
+    })
+}
+
+fn tmx_attribute<T: std::str::FromStr>(node: Node, name: &str) -> Result<T> {
+    let value = node
+        .attribute(name)
+        .ok_or_else(|| eyre!("{} element is missing {}", node.tag_name().name(), name))?;
+    value.parse().map_err(|_| {
+        eyre!(
+            "{} of {} element is not a number: {:?}",
+            name,
+            node.tag_name().name(),
+            value
+        )
+    })
+}
+
+/// Tiles are placed by their index in the layer, which needs the width of the layer and a tile id for every cell
+fn check_tile_layer(name: &str, width: u32, height: u32, tile_count: usize) -> Result<()> {
+    if width == 0 {
+        bail!("tile layer {:?} needs a width", name);
+    }
+    let cell_count = width as usize * height as usize;
+    if tile_count != cell_count {
+        bail!(
+            "tile layer {:?} has {} tile ids, its size of {}x{} needs {}",
+            name,
+            tile_count,
+            width,
+            height,
+            cell_count
+        );
+    }
+    Ok(())
+}
+
+/// Tile layers named background or foreground are drawn in that layer, the rest are drawn with the tiles
+fn tile_render_layer(name: &str) -> RenderLayer {
+    match name.to_lowercase().as_str() {
+        "background" => RenderLayer::Background,
+        "foreground" => RenderLayer::Foreground,
+        _ => RenderLayer::Tiles,
+    }
+}
+
+/// A custom property named type, for maps that set the type that way instead of with the built in field
+fn tmx_type_property<'a>(object: Node<'a, '_>) -> Option<&'a str> {
+    object
+        .children()
+        .find(|node| node.has_tag_name("properties"))?
+        .children()
+        .find(|property| property.attribute("name") == Some("type"))?
+        .attribute("value")
+}
+
+#[derive(Deserialize)]
+struct TmjMap {
+    width: u32,
+    height: u32,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    infinite: bool,
+    layers: Vec<TmjLayer>,
+}
+
+#[derive(Deserialize)]
+struct TmjLayer {
+    #[serde(rename = "type")]
+    layer_type: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    data: Option<Value>,
+    encoding: Option<String>,
+    #[serde(default)]
+    objects: Vec<TmjObject>,
+}
+
+#[derive(Deserialize)]
+struct TmjObject {
+    id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "type")]
+    object_type: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    ellipse: bool,
+    polygon: Option<Value>,
+    polyline: Option<Value>,
+    text: Option<Value>,
+    gid: Option<u32>,
+    #[serde(default)]
+    properties: Vec<TmjProperty>,
+}
+
+#[derive(Deserialize)]
+struct TmjProperty {
+    name: String,
+    value: Value,
+}
+
+fn parse_tmj(tmj: &str) -> Result<TiledMap> {
+    let map: TmjMap = serde_json::from_str(tmj).map_err(|error| {
+        eyre!(
+            "invalid tmj file at line {}, column {}: {}",
+            error.line(),
+            error.column(),
+            error
+        )
+    })?;
+    if map.infinite {
+        bail!("infinite tiled maps are not supported");
+    }
+
+    let mut layers = vec![];
+    for layer in map.layers {
+        let name = layer.name;
+        match layer.layer_type.as_str() {
+            "tilelayer" => {
+                if let Some(encoding) = layer.encoding.filter(|encoding| encoding != "csv") {
+                    bail!(
+                        "tile layer {:?} uses {} encoding, save the map with CSV tile layer format",
+                        name,
+                        encoding
+                    );
+                }
+                let data = layer
+                    .data
+                    .ok_or_else(|| eyre!("tile layer {:?} has no data", name))?;
+                let data: Vec<u32> = serde_json::from_value(data)
+                    .map_err(|_| eyre!("tile layer {:?} data is not a list of tile ids", name))?;
+                check_tile_layer(&name, layer.width, layer.height, data.len())?;
+                layers.push(TiledLayer::Tiles {
+                    width: layer.width,
+                    data,
+                    layer: tile_render_layer(&name),
+                });
+            }
+            "objectgroup" => {
+                let objects = layer
+                    .objects
+                    .into_iter()
+                    .map(|object| {
+                        if object.gid.is_some() {
+                            bail!("tile object {} is not supported", object.id);
+                        }
+                        if object.ellipse
+                            || object.polygon.is_some()
+                            || object.polyline.is_some()
+                            || object.text.is_some()
+                        {
+                            bail!(
+                                "object {} is not supported, only rectangles and points are",
+                                object.id
+                            );
+                        }
+                        let type_property = object
+                            .properties
+                            .iter()
+                            .find(|property| property.name == "type")
+                            .and_then(|property| property.value.as_str());
+                        let object_type = if !object.object_type.is_empty() {
+                            object.object_type
+                        } else if !object.class.is_empty() {
+                            object.class
+                        } else {
+                            type_property.unwrap_or_default().to_owned()
+                        };
+                        Ok(TiledObject {
+                            id: object.id,
+                            name: object.name,
+                            object_type,
+                            x: object.x,
+                            y: object.y,
+                            width: object.width,
+                            height: object.height,
+                        })
+                    })
+                    .collect::<Result<Vec<TiledObject>>>()?;
+                layers.push(TiledLayer::Objects(objects));
+            }
+            layer_type => bail!(
+                "{} {:?} is not supported, only tile layers and object layers are",
+                layer_type,
+                name
+            ),
+        }
+    }
+
+    Ok(TiledMap {
+        width: map.width,
+        height: map.height,
+        tile_width: map.tilewidth,
+        tile_height: map.tileheight,
+        layers,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.4" orientation="orthogonal" width="4" height="3" tilewidth="16" tileheight="16" infinite="0">
+ <layer id="1" name="ground" width="4" height="3">
+  <data encoding="csv">
+0,0,0,0,
+0,0,0,3,
+1,2,2,2147483650
+</data>
+ </layer>
+ <objectgroup id="2" name="objects">
+  <object id="1" name="player" type="spawn" x="8" y="8">
+   <point/>
+  </object>
+  <object id="2" type="platform" x="16" y="0" width="32" height="8"/>
+ </objectgroup>
+</map>
+"#;
+
+    const TMJ: &str = r#"{
+        "width": 4, "height": 3, "tilewidth": 16, "tileheight": 16, "infinite": false,
+        "layers": [
+            {"type": "tilelayer", "name": "ground", "width": 4, "height": 3,
+             "data": [0, 0, 0, 0, 0, 0, 0, 3, 1, 2, 2, 2147483650]},
+            {"type": "objectgroup", "name": "objects", "objects": [
+                {"id": 1, "name": "", "class": "player", "x": 8, "y": 8, "point": true},
+                {"id": 2, "name": "", "x": 16, "y": 0, "width": 32, "height": 8,
+                 "properties": [{"name": "type", "type": "string", "value": "platform"}]}
+            ]}
+        ]
+    }"#;
+
+    fn assert_example_level(level: &Level) {
+        assert_eq!((level.width, level.height), (200.0, 150.0));
+        let tiles: Vec<(f32, f32)> = level
+            .entity_datas
+            .iter()
+            .filter(|entity_data| entity_data.width == 50.0 && entity_data.height == 50.0)
+            .map(|entity_data| (entity_data.x, entity_data.y))
+            .collect();
+        assert_eq!(
+            tiles,
+            vec![
+                (175.0, 75.0),
+                (25.0, 125.0),
+                (75.0, 125.0),
+                (125.0, 125.0),
+                (175.0, 125.0)
+            ]
+        );
+
+        let platform = level.entity_datas.last().unwrap();
+        assert_eq!(platform.entity_type, EntityType::Platform);
+        assert_eq!(
+            (platform.x, platform.y, platform.width, platform.height),
+            (100.0, 12.5, 100.0, 25.0)
+        );
+
+        let spawn_point = level.player_spawn_point().unwrap();
+        assert_eq!((spawn_point.x, spawn_point.y), (25.0, 25.0));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_import_tmx() {
+        let level = import_tmx_str(TMX, 50.0, 50.0).unwrap();
+        assert_example_level(&level);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_import_tmj() {
+        let level = import_tmj_str(TMJ, 50.0, 50.0).unwrap();
+        assert_example_level(&level);
+    }
+
+    #[test]
+    fn ci_test_import_tmx_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/levels/tiled_example.tmx");
+        let level = import(path, 50.0, 50.0).unwrap();
+        assert!(!level.entity_datas.is_empty());
+        assert!(level.player_spawn_point().is_some());
+    }
+
+    #[test]
+    fn ci_test_tile_layers_named_after_render_layers() {
+        let tmx = TMX.replace(r#"name="ground""#, r#"name="Foreground""#);
+        let level = import_tmx_str(&tmx, 50.0, 50.0).unwrap();
+        assert_eq!(level.entity_datas[0].layer, Some(RenderLayer::Foreground));
+
+        let level = import_tmx_str(TMX, 50.0, 50.0).unwrap();
+        assert_eq!(level.entity_datas[0].layer(), RenderLayer::Tiles);
+    }
+
+    #[test]
+    fn ci_test_unknown_object_types_are_errors() {
+        let tmx = TMX.replace(r#"type="platform""#, r#"type="door""#);
+        let error = import_tmx_str(&tmx, 50.0, 50.0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"tiled object 2 "" has the type "door" which is not supported, use one of: platform, goal, spawn, player"#
+        );
+    }
+
+    #[test]
+    fn ci_test_unsupported_tmx_features_are_errors() {
+        let base64 = TMX.replace(r#"encoding="csv""#, r#"encoding="base64""#);
+        let error = import_tmx_str(&base64, 50.0, 50.0).unwrap_err();
+        assert!(error.to_string().contains("base64 encoding"), "{}", error);
+
+        let infinite = TMX.replace(r#"infinite="0""#, r#"infinite="1""#);
+        let error = import_tmx_str(&infinite, 50.0, 50.0).unwrap_err();
+        assert_eq!(error.to_string(), "infinite tiled maps are not supported");
+
+        let ellipse = TMX.replace("<point/>", "<ellipse/>");
+        let error = import_tmx_str(&ellipse, 50.0, 50.0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "ellipse object at line 11, column 3 is not supported, only rectangles and points are"
+        );
+
+        let image_layer = TMX.replace(
+            "</map>",
+            r#"<imagelayer id="3" name="sky"><image source="sky.png"/></imagelayer></map>"#,
+        );
+        let error = import_tmx_str(&image_layer, 50.0, 50.0).unwrap_err();
+        assert!(
+            error.to_string().starts_with("imagelayer at line 16"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn ci_test_unsupported_tmj_features_are_errors() {
+        let group = TMJ.replace(r#""type": "objectgroup""#, r#""type": "group""#);
+        let error = import_tmj_str(&group, 50.0, 50.0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"group "objects" is not supported, only tile layers and object layers are"#
+        );
+
+        let missing_type = TMJ.replace(r#""class": "player", "#, "");
+        let error = import_tmj_str(&missing_type, 50.0, 50.0).unwrap_err();
+        assert!(error.to_string().contains("needs a type"), "{}", error);
+    }
+
+    #[test]
+    fn ci_test_tile_layers_need_a_size_that_fits_their_data() {
+        let no_width = TMJ.replace(r#""name": "ground", "width": 4,"#, r#""name": "ground","#);
+        let error = import_tmj_str(&no_width, 50.0, 50.0).unwrap_err();
+        assert_eq!(error.to_string(), r#"tile layer "ground" needs a width"#);
+
+        let zero_width = TMX.replace(r#"name="ground" width="4""#, r#"name="ground" width="0""#);
+        let error = import_tmx_str(&zero_width, 50.0, 50.0).unwrap_err();
+        assert_eq!(error.to_string(), r#"tile layer "ground" needs a width"#);
+
+        let too_short = TMX.replace(
+            r#"name="ground" width="4" height="3""#,
+            r#"name="ground" width="4" height="4""#,
+        );
+        let error = import_tmx_str(&too_short, 50.0, 50.0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"tile layer "ground" has 12 tile ids, its size of 4x4 needs 16"#
+        );
+    }
+}
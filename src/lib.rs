@@ -1,107 +1,172 @@
-mod camera;
-mod config;
+pub mod ai_system;
+pub mod animation;
+pub mod camera;
+pub mod config;
+pub mod debug_overlay;
 mod draw_system;
-mod drawables;
+pub mod drawables;
 pub mod entity;
-mod level;
-mod physics_system;
-mod world;
+pub mod hot_reload;
+pub mod input;
+pub mod level;
+pub mod physics_system;
+pub mod replay;
+pub mod scene;
+pub mod viewport;
+pub mod world;
+
+use std::path::PathBuf;
 
-use camera::Camera;
 use config::Config;
-use draw_system::player_draw_system::PlayerDrawSystem;
-use drawables::Drawables;
-use entity::builder::EntityBuilder;
-use entity::entity_data::EntityData;
-use entity::entity_type::EntityType;
 pub use entity::Entity;
-use ggez::event::EventHandler;
-use ggez::graphics::{Color, BLACK};
+use ggez::event::{Axis, Button, EventHandler, GamepadId, KeyCode, KeyMods};
+use ggez::graphics::{self, DrawMode, DrawParam, FilterMode, MeshBuilder, BLACK};
 use ggez::timer::check_update_time;
 use ggez::{Context, GameResult};
-use level::Level;
-use physics_system::player_physics_system::PlayerPhysicsSystem;
-use world::World;
+use input::bindings::InputBindings;
+use input::InputState;
+use scene::gameplay_scene::GameplayScene;
+use scene::scene_stack::SceneStack;
+use scene::title_scene::TitleScene;
+use scene::SceneInput;
+use viewport::scaling_mode::ScalingMode;
+use viewport::Viewport;
 
 pub struct GameState {
-    world: World,
-    drawables: Drawables,
+    scenes: SceneStack,
     target_update_fps: u32,
-    entity_builder: EntityBuilder,
+    input_bindings: InputBindings,
+    input: InputState,
+    viewport: Viewport,
 }
 
 impl GameState {
-    pub fn new(context: &mut Context) -> GameResult<Self> {
-        let config = Config::default();
-        let level = Level::new(
-            5000.0,
-            5000.0,
-            vec![
-                EntityData::new(
-                    config.player_start_x,
-                    500.0,
-                    config.world_unit_width,
-                    config.world_unit_height,
-                    Color::new(0.0, 1.0, 0.0, 1.0),
-                    EntityType::Platform,
-                ),
-                EntityData::new(
-                    500.0,
-                    500.0,
-                    config.world_unit_width,
-                    config.world_unit_height,
-                    Color::new(1.0, 0.0, 0.0, 1.0),
-                    EntityType::Platform,
-                ),
-            ],
+    /// Start on the title, or straight in the game when playing a replay. The config path is watched for
+    /// changes when the config turns hot reloading on.
+    pub fn new(
+        context: &mut Context,
+        config: &Config,
+        config_path: Option<PathBuf>,
+    ) -> GameResult<Self> {
+        let mut viewport = Viewport::new(
+            config.virtual_width,
+            config.virtual_height,
+            config.scaling_mode,
         );
-        let camera = Camera::new(0.0, 0.0, 1280.0, 720.0);
-        let mut world = World::new();
-        let mut entity_builder = EntityBuilder::new();
-        world
-            .set_gravity(config.gravity_force)
-            .set_size(config.world_width, config.world_height)
-            .set_unit_size(config.world_unit_width, config.world_unit_height)
-            .add_level(level)
-            .set_camera(camera)
-            .build(&mut entity_builder);
-        let drawables = Drawables::new(context, &world, &config)?;
-        let target_update_fps = config.target_update_fps;
-
-        let player = entity_builder
-            .create_entity()
-            .location(config.player_start_x, config.player_start_y)
-            .size(config.player_width, config.player_height)
-            .draw_system(Box::new(PlayerDrawSystem))
-            .affected_by_gravity()
-            .physics_system(Box::new(PlayerPhysicsSystem::default()))
-            .build();
-        world.add_entity(player);
+        let (window_width, window_height) = graphics::drawable_size(context);
+        viewport.resize(window_width, window_height);
+        if config.scaling_mode == ScalingMode::PixelPerfect {
+            graphics::set_default_filter(context, FilterMode::Nearest);
+        }
+        let mut scenes = SceneStack::new();
+        let (width, height) = viewport.logical_size();
+        scenes.input(&SceneInput::Resized { width, height });
+        if config.play_replay_path.is_some() {
+            scenes.push(Box::new(GameplayScene::new(context, config, config_path)?));
+        } else {
+            scenes.push(Box::new(TitleScene::new(config.clone(), config_path)));
+        }
 
         Ok(Self {
-            world,
-            drawables,
-            target_update_fps,
-            entity_builder,
+            scenes,
+            target_update_fps: config.target_update_fps,
+            input_bindings: InputBindings::default(),
+            input: InputState::new(),
+            viewport,
         })
     }
+
+    /// Take every scene off the stack so they can clean up, such as saving a replay. Call this after the game
+    /// exits.
+    pub fn exit(&mut self) {
+        self.scenes.clear();
+    }
 }
 
 impl EventHandler for GameState {
     fn update(&mut self, context: &mut Context) -> GameResult {
+        if let Some(target_update_fps) = self.scenes.target_update_fps() {
+            self.target_update_fps = target_update_fps;
+        }
+        // time keeps being used up while a scene is paused underneath another, so it does not catch up later
         while check_update_time(context, self.target_update_fps) {
-            self.world.update();
+            self.scenes.update(context, &self.input)?;
+            self.input.end_tick();
+        }
+        if self.scenes.is_empty() {
+            ggez::event::quit(context);
         }
         Ok(())
     }
 
     fn draw(&mut self, context: &mut Context) -> GameResult {
-        ggez::graphics::clear(context, BLACK);
+        graphics::set_screen_coordinates(context, self.viewport.screen_coordinates())?;
+        graphics::clear(context, BLACK);
 
         let lag = ggez::timer::remaining_update_time(context).as_secs_f32()
             * self.target_update_fps as f32;
-        self.world.draw(context, &self.drawables, lag)?;
+        self.scenes.draw(context, lag)?;
+
+        // cover up anything in the world that was drawn outside of the virtual resolution
+        let bars = self.viewport.bars();
+        if !bars.is_empty() {
+            let mut mesh_builder = MeshBuilder::new();
+            for bar in bars {
+                mesh_builder.rectangle(DrawMode::fill(), bar, BLACK);
+            }
+            let mesh = mesh_builder.build(context)?;
+            graphics::draw(context, &mesh, DrawParam::new())?;
+        }
+
+        graphics::present(context)
+    }
+
+    fn resize_event(&mut self, _context: &mut Context, width: f32, height: f32) {
+        self.viewport.resize(width, height);
+        let (width, height) = self.viewport.logical_size();
+        self.scenes.input(&SceneInput::Resized { width, height });
+    }
+
+    fn mouse_motion_event(&mut self, _context: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        let location = self.viewport.window_to_virtual(x, y);
+        self.scenes.input(&SceneInput::MouseMoved(location));
+    }
+
+    fn key_down_event(
+        &mut self,
+        _context: &mut Context,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        repeat: bool,
+    ) {
+        self.scenes.input(&SceneInput::KeyDown { keycode, repeat });
+        self.input.key_down(&self.input_bindings, keycode);
+    }
+
+    fn key_up_event(&mut self, _context: &mut Context, keycode: KeyCode, _keymods: KeyMods) {
+        self.input.key_up(keycode);
+    }
+
+    fn gamepad_button_down_event(
+        &mut self,
+        _context: &mut Context,
+        button: Button,
+        _id: GamepadId,
+    ) {
+        self.input.button_down(&self.input_bindings, button);
+    }
+
+    fn gamepad_button_up_event(&mut self, _context: &mut Context, button: Button, _id: GamepadId) {
+        self.input.button_up(button);
+    }
 
-        ggez::graphics::present(context)
+    fn gamepad_axis_event(
+        &mut self,
+        _context: &mut Context,
+        axis: Axis,
+        value: f32,
+        _id: GamepadId,
+    ) {
+        self.input.axis_moved(&self.input_bindings, axis, value);
     }
 }
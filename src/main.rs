@@ -1,30 +1,33 @@
+use game_template_platform::config::Config;
 use game_template_platform::GameState;
-use ggez::conf::{Backend, WindowMode, WindowSetup};
-use ggez::{event, ContextBuilder, GameResult};
+use ggez::conf::{WindowMode, WindowSetup};
+use ggez::{event, ContextBuilder, GameError, GameResult};
 
 fn main() -> GameResult {
+    let (config, config_path) = Config::load_from_environment()
+        .map_err(|error| GameError::ConfigError(format!("{:#}", error)))?;
     let window_mode = WindowMode::default()
-        .dimensions(1280.0, 720.0)
-        .maximized(true)
-        .resizable(true);
-    let backend = Backend::default().version(3, 1).gles();
-    let window_setup = WindowSetup::default().vsync(false);
+        .dimensions(config.window_width, config.window_height)
+        .maximized(config.window_maximized)
+        .resizable(config.window_resizable);
+    let window_setup = WindowSetup::default().vsync(config.vsync);
     let (context, event_loop) = &mut match ContextBuilder::new("Platform Template", "Brooks Patton")
         .window_mode(window_mode)
-        .backend(backend)
+        .backend(config.graphics_backend.into())
         .window_setup(window_setup)
         .build()
     {
         Ok((context, event_loop)) => (context, event_loop),
-        Err(error) => panic!(error),
+        Err(error) => panic!("{}", error),
     };
 
-    let game_state = &mut GameState::new(context)?;
+    let game_state = &mut GameState::new(context, &config, Some(config_path))?;
 
     match event::run(context, event_loop, game_state) {
         Ok(_) => println!("Thanks for playing!"),
         Err(error) => println!("Error occurred: {}", error),
     };
+    game_state.exit();
 
     Ok(())
 }
@@ -0,0 +1,79 @@
+use ggez::nalgebra::Vector2;
+
+use crate::entity::Entity;
+
+use super::contacts::Contacts;
+
+/// How far two boxes need to overlap before it counts as a collision. Without it an entity resting exactly on
+/// top of a platform could be seen as overlapping because of floating point rounding.
+const COLLISION_TOLERANCE: f32 = 0.001;
+
+fn overlaps_with(our_location: &Vector2<f32>, width: f32, height: f32, other: &Entity) -> bool {
+    // is our right side to the right of the others left
+    our_location.x + width / 2.0 > other.location.x - other.width / 2.0 + COLLISION_TOLERANCE
+    // and is our left side to the left of the others right
+    && our_location.x - width / 2.0 < other.location.x + other.width / 2.0 - COLLISION_TOLERANCE
+    // and is our bottom below the others top
+    && our_location.y + height / 2.0 > other.location.y - other.height / 2.0 + COLLISION_TOLERANCE
+    // and is our top above the others bottom?
+    && our_location.y - height / 2.0 < other.location.y +  other.height / 2.0 - COLLISION_TOLERANCE
+}
+
+/// Move along the x axis and push back out of anything we ran into
+pub fn move_horizontally(
+    location: &mut Vector2<f32>,
+    (width, height): (f32, f32),
+    velocity: &mut Vector2<f32>,
+    others: &[&Entity],
+    contacts: &mut Contacts,
+) {
+    location.x += velocity.x;
+
+    for other in others.iter().filter(|other| other.collidable) {
+        if !overlaps_with(location, width, height, other) {
+            continue;
+        }
+
+        if velocity.x > 0.0 {
+            location.x = other.location.x - other.width / 2.0 - width / 2.0;
+            contacts.right = true;
+        } else if velocity.x < 0.0 {
+            location.x = other.location.x + other.width / 2.0 + width / 2.0;
+            contacts.left = true;
+        }
+    }
+
+    if contacts.left || contacts.right {
+        // only the horizontal movement stops so that we slide along walls
+        velocity.x = 0.0;
+    }
+}
+
+/// Move along the y axis and push back out of anything we landed on or bonked our head against
+pub fn move_vertically(
+    location: &mut Vector2<f32>,
+    (width, height): (f32, f32),
+    velocity: &mut Vector2<f32>,
+    others: &[&Entity],
+    contacts: &mut Contacts,
+) {
+    location.y += velocity.y;
+
+    for other in others.iter().filter(|other| other.collidable) {
+        if !overlaps_with(location, width, height, other) {
+            continue;
+        }
+
+        if velocity.y > 0.0 {
+            location.y = other.location.y - other.height / 2.0 - height / 2.0;
+            contacts.bottom = true;
+        } else if velocity.y < 0.0 {
+            location.y = other.location.y + other.height / 2.0 + height / 2.0;
+            contacts.top = true;
+        }
+    }
+
+    if contacts.top || contacts.bottom {
+        velocity.y = 0.0;
+    }
+}
@@ -0,0 +1,33 @@
+/// The sides of an entity that touched something collidable during the last physics update
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Contacts {
+    pub top: bool,
+    pub bottom: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+impl Contacts {
+    pub fn any(&self) -> bool {
+        self.top || self.bottom || self.left || self.right
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ci_test_contacts_any() {
+        let mut contacts = Contacts::default();
+        assert!(!contacts.any());
+        contacts.left = true;
+        assert!(contacts.any());
+        contacts.clear();
+        assert_eq!(contacts, Contacts::default());
+    }
+}
@@ -0,0 +1,137 @@
+use ggez::nalgebra::Vector2;
+
+use crate::entity::entity_state::EntityState;
+use crate::entity::Entity;
+
+use super::collisions;
+use super::contacts::Contacts;
+use super::PhysicsSystem;
+
+/// Moves an entity the way its AI steers it. Flying enemies go where they are steered on both axes, walking
+/// ones only choose how fast they go sideways and fall like everything else.
+#[derive(Debug)]
+pub struct EnemyPhysicsSystem {
+    acceleration: Vector2<f32>,
+    velocity: Vector2<f32>,
+    steering: Vector2<f32>,
+    contacts: Contacts,
+    flying: bool,
+}
+
+impl EnemyPhysicsSystem {
+    pub fn new(flying: bool) -> Self {
+        Self {
+            acceleration: Vector2::new(0.0, 0.0),
+            velocity: Vector2::new(0.0, 0.0),
+            steering: Vector2::new(0.0, 0.0),
+            contacts: Contacts::default(),
+            flying,
+        }
+    }
+}
+
+impl PhysicsSystem for EnemyPhysicsSystem {
+    fn apply_force(&mut self, force: &Vector2<f32>) {
+        self.acceleration += force;
+    }
+
+    fn steer(&mut self, velocity: &Vector2<f32>) {
+        self.steering = *velocity;
+    }
+
+    fn update(
+        &mut self,
+        location: &mut Vector2<f32>,
+        width: f32,
+        height: f32,
+        others: &[&Entity],
+        state: &mut EntityState,
+    ) {
+        self.velocity.x = self.steering.x;
+        self.velocity.y = if self.flying {
+            self.steering.y
+        } else {
+            (self.velocity.y + self.acceleration.y).clamp(-10.0, 10.0)
+        };
+        self.acceleration *= 0.0;
+        self.contacts.clear();
+
+        collisions::move_horizontally(
+            location,
+            (width, height),
+            &mut self.velocity,
+            others,
+            &mut self.contacts,
+        );
+        collisions::move_vertically(
+            location,
+            (width, height),
+            &mut self.velocity,
+            others,
+            &mut self.contacts,
+        );
+
+        *state = if self.contacts.bottom {
+            EntityState::Standing
+        } else if self.flying {
+            EntityState::None
+        } else {
+            EntityState::Falling
+        };
+    }
+
+    fn get_velocity(&self) -> &Vector2<f32> {
+        &self.velocity
+    }
+
+    fn get_contacts(&self) -> &Contacts {
+        &self.contacts
+    }
+
+    fn reset(&mut self) {
+        self.acceleration *= 0.0;
+        self.velocity *= 0.0;
+        self.steering *= 0.0;
+        self.contacts.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::builder::EntityBuilder;
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_walking_enemies_fall_and_flying_ones_do_not() {
+        let mut entity_builder = EntityBuilder::new();
+        let floor = entity_builder
+            .create_entity()
+            .location(0.0, 20.0)
+            .size(100.0, 10.0)
+            .collidable()
+            .build();
+        let steering = Vector2::new(1.0, -1.0);
+        let gravity = Vector2::new(0.0, 1.0);
+
+        let mut walker = EnemyPhysicsSystem::new(false);
+        let mut location = Vector2::new(0.0, 5.0);
+        let mut state = EntityState::None;
+        for _ in 0..5 {
+            walker.steer(&steering);
+            walker.apply_force(&gravity);
+            walker.update(&mut location, 10.0, 10.0, &[&floor], &mut state);
+        }
+        assert_eq!(location, Vector2::new(5.0, 10.0));
+        assert_eq!(state, EntityState::Standing);
+
+        let mut flyer = EnemyPhysicsSystem::new(true);
+        let mut location = Vector2::new(0.0, 5.0);
+        flyer.steer(&steering);
+        flyer.apply_force(&gravity);
+        flyer.update(&mut location, 10.0, 10.0, &[&floor], &mut state);
+        assert_eq!(location, Vector2::new(1.0, 4.0));
+        assert_eq!(state, EntityState::None);
+    }
+}
@@ -1,21 +1,38 @@
+pub mod collisions;
+pub mod contacts;
+pub mod enemy_physics_system;
 pub mod player_physics_system;
 
 use std::fmt::Debug;
 
+use crate::config::Config;
 use crate::entity::Entity;
+use crate::input::InputState;
+
+use self::contacts::Contacts;
 
 pub trait PhysicsSystem
 where
     Self: Debug,
 {
     fn apply_force(&mut self, force: &ggez::nalgebra::Vector2<f32>);
+    /// Turn the actions of whoever is controlling the entity into forces. Called before each update.
+    fn handle_input(&mut self, _input: &InputState) {}
+    /// Move at this many units per tick, chosen by the AI system of the entity. Called before each update.
+    fn steer(&mut self, _velocity: &ggez::nalgebra::Vector2<f32>) {}
     fn update(
         &mut self,
         location: &mut ggez::nalgebra::Vector2<f32>,
         width: f32,
         height: f32,
-        others: Vec<Entity>,
+        others: &[&Entity],
         state: &mut crate::entity::entity_state::EntityState,
     );
     fn get_velocity(&self) -> &ggez::nalgebra::Vector2<f32>;
+    /// The sides that were blocked by another entity during the last update
+    fn get_contacts(&self) -> &Contacts;
+    /// Forget all movement, used when the entity is placed somewhere new such as a respawn
+    fn reset(&mut self) {}
+    /// Take the tuning values from a config that changed while the game is running, keeping the movement
+    fn apply_config(&mut self, _config: &Config) {}
 }
@@ -1,44 +1,131 @@
 use ggez::nalgebra::Vector2;
 
+use crate::config::Config;
 use crate::entity::entity_state::EntityState;
 use crate::entity::Entity;
+use crate::input::action::Action;
+use crate::input::InputState;
 
+use super::collisions;
+use super::contacts::Contacts;
 use super::PhysicsSystem;
 
 #[derive(Debug)]
 pub struct PlayerPhysicsSystem {
     acceleration: Vector2<f32>,
     velocity: Vector2<f32>,
+    contacts: Contacts,
+    run_force: f32,
+    max_run_speed: f32,
+    friction: f32,
+    jump_force: f32,
+    jump_hold_force: f32,
+    max_jump_hold_ticks: u32,
+    coyote_time_ticks: u32,
+    jump_buffer_ticks: u32,
+    /// -1.0 when running left, 1.0 when running right
+    run_direction: f32,
+    crouching: bool,
+    jump_held: bool,
+    /// true from the tick we leave the ground until the jump stops being boosted
+    jumping: bool,
+    jump_hold_ticks: u32,
+    /// ticks left where we can still jump after walking off of a ledge
+    coyote_ticks_left: u32,
+    /// ticks left where a jump press is remembered until we can jump
+    buffered_jump_ticks_left: u32,
 }
 
 impl PlayerPhysicsSystem {
-    fn overlaps_with(
-        &self,
-        our_location: &Vector2<f32>,
-        width: f32,
-        height: f32,
-        other: &Entity,
-    ) -> bool {
-        // is our right side to the right of the others left
-        our_location.x + width / 2.0 > other.location.x - other.width / 2.0
-        // and is our left side to the left of the others right
-        && our_location.x - width / 2.0 < other.location.x + other.width / 2.0
-        // and is our bottom below the others top
-        && our_location.y + height / 2.0 > other.location.y - other.height / 2.0
-        // and is our top above the others bottom?
-        && our_location.y - height / 2.0 < other.location.y +  other.height / 2.0
+    pub fn new(config: &Config) -> Self {
+        let mut physics_system = Self {
+            acceleration: Vector2::new(0.0, 0.0),
+            velocity: Vector2::new(0.0, 0.0),
+            contacts: Contacts::default(),
+            run_force: 0.0,
+            max_run_speed: 0.0,
+            friction: 0.0,
+            jump_force: 0.0,
+            jump_hold_force: 0.0,
+            max_jump_hold_ticks: 0,
+            coyote_time_ticks: 0,
+            jump_buffer_ticks: 0,
+            run_direction: 0.0,
+            crouching: false,
+            jump_held: false,
+            jumping: false,
+            jump_hold_ticks: 0,
+            coyote_ticks_left: 0,
+            buffered_jump_ticks_left: 0,
+        };
+        physics_system.apply_config(config);
+        physics_system
+    }
+
+    /// Turn the latest running input into changes in velocity
+    fn apply_running(&mut self, state: EntityState) {
+        if self.run_direction != 0.0 && !self.crouching {
+            self.velocity.x = (self.velocity.x + self.run_direction * self.run_force)
+                .clamp(-self.max_run_speed, self.max_run_speed);
+        } else if state == EntityState::Standing {
+            self.velocity.x *= self.friction;
+            if self.velocity.x.abs() < 0.01 {
+                self.velocity.x = 0.0;
+            }
+        }
+    }
+
+    /// Start a jump if one was pressed recently and we are on, or just left, the ground. A jump that has
+    /// started keeps getting pushed up for as long as the button is held, up to the max hold time.
+    fn apply_jumping(&mut self, state: EntityState) {
+        let can_jump = state == EntityState::Standing || self.coyote_ticks_left > 0;
+        if self.buffered_jump_ticks_left > 0 && can_jump {
+            self.velocity.y = -self.jump_force;
+            self.jumping = true;
+            self.jump_hold_ticks = 0;
+            self.buffered_jump_ticks_left = 0;
+            self.coyote_ticks_left = 0;
+        } else if self.jumping {
+            if self.jump_held && self.jump_hold_ticks < self.max_jump_hold_ticks {
+                self.velocity.y -= self.jump_hold_force;
+                self.jump_hold_ticks += 1;
+            } else {
+                self.jumping = false;
+            }
+        }
+
+        self.buffered_jump_ticks_left = self.buffered_jump_ticks_left.saturating_sub(1);
+    }
+
+    /// Work out the new state from how we collided and which way we are moving
+    fn next_state(&mut self) -> EntityState {
+        if self.contacts.bottom {
+            self.jumping = false;
+            self.coyote_ticks_left = self.coyote_time_ticks;
+            return EntityState::Standing;
+        }
+
+        self.coyote_ticks_left = self.coyote_ticks_left.saturating_sub(1);
+        if self.contacts.top {
+            self.jumping = false;
+        }
+
+        if self.velocity.y < 0.0 {
+            if self.jumping {
+                EntityState::Jumping
+            } else {
+                EntityState::Rising
+            }
+        } else {
+            // walking off of a ledge and bonking our head both end with us falling
+            EntityState::Falling
+        }
     }
 }
 
 impl Default for PlayerPhysicsSystem {
     fn default() -> Self {
-        let acceleration = Vector2::new(0.0, 0.0);
-        let velocity = Vector2::new(0.0, 0.0);
-
-        Self {
-            acceleration,
-            velocity,
-        }
+        Self::new(&Config::default())
     }
 }
 
@@ -47,40 +134,534 @@ impl PhysicsSystem for PlayerPhysicsSystem {
         self.acceleration += force;
     }
 
+    fn handle_input(&mut self, input: &InputState) {
+        self.run_direction = match (
+            input.is_held(Action::MoveLeft),
+            input.is_held(Action::MoveRight),
+        ) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+        self.crouching = input.is_held(Action::Crouch);
+        self.jump_held = input.is_held(Action::Jump);
+        if input.was_pressed(Action::Jump) {
+            // the extra tick is the one the press happened on
+            self.buffered_jump_ticks_left = self.jump_buffer_ticks + 1;
+        }
+    }
+
+    /// Resolve collisions one axis at a time, first x and then y. Resolving the axes separately means that
+    /// running into a wall does not stop us from falling and landing does not stop us from running.
     fn update(
         &mut self,
         location: &mut ggez::nalgebra::Vector2<f32>,
         width: f32,
         height: f32,
-        others: Vec<Entity>,
+        others: &[&Entity],
         state: &mut crate::entity::entity_state::EntityState,
     ) {
         self.velocity += self.acceleration;
-        if self.velocity.y > 10.0 {
-            self.velocity.y = 10.0;
-        } else if self.velocity.y < -10.0 {
-            self.velocity.y = -10.0;
-        }
-        location.x += self.velocity.x;
-        location.y += self.velocity.y;
+        self.apply_running(*state);
+        self.apply_jumping(*state);
+        self.velocity.y = self.velocity.y.clamp(-10.0, 10.0);
+        self.acceleration *= 0.0;
+        self.contacts.clear();
+
+        collisions::move_horizontally(
+            location,
+            (width, height),
+            &mut self.velocity,
+            others,
+            &mut self.contacts,
+        );
+        collisions::move_vertically(
+            location,
+            (width, height),
+            &mut self.velocity,
+            others,
+            &mut self.contacts,
+        );
+
+        *state = self.next_state();
+    }
+
+    fn get_velocity(&self) -> &Vector2<f32> {
+        &self.velocity
+    }
+
+    fn get_contacts(&self) -> &Contacts {
+        &self.contacts
+    }
+
+    fn reset(&mut self) {
         self.acceleration *= 0.0;
+        self.velocity *= 0.0;
+        self.contacts.clear();
+        self.jumping = false;
+        self.jump_hold_ticks = 0;
+        self.coyote_ticks_left = 0;
+        self.buffered_jump_ticks_left = 0;
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.run_force = config.player_run_force;
+        self.max_run_speed = config.player_max_run_speed;
+        self.friction = config.player_friction;
+        self.jump_force = config.player_jump_force;
+        self.jump_hold_force = config.player_jump_hold_force;
+        self.max_jump_hold_ticks = config.player_max_jump_hold_ticks;
+        self.coyote_time_ticks = config.coyote_time_ticks;
+        self.jump_buffer_ticks = config.jump_buffer_ticks;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::builder::EntityBuilder;
+    use crate::input::bindings::InputBindings;
+
+    use super::*;
+
+    fn create_platform(entity_builder: &mut EntityBuilder, x: f32, y: f32) -> Entity {
+        entity_builder
+            .create_entity()
+            .location(x, y)
+            .size(50.0, 50.0)
+            .collidable()
+            .build()
+    }
+
+    fn update_with_velocity(
+        physics_system: &mut PlayerPhysicsSystem,
+        location: &mut Vector2<f32>,
+        velocity: Vector2<f32>,
+        others: &[&Entity],
+    ) -> EntityState {
+        let mut state = EntityState::None;
+        physics_system.apply_force(&velocity);
+        physics_system.update(location, 50.0, 100.0, others, &mut state);
+        state
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_landing_on_top_of_a_platform() {
+        let mut entity_builder = EntityBuilder::new();
+        let platform = create_platform(&mut entity_builder, 100.0, 200.0);
+        let mut physics_system = PlayerPhysicsSystem::default();
+        // our bottom starts at 172 and ends up inside of the platform top at 175 after moving 5 down
+        let mut location = Vector2::new(100.0, 122.0);
+        let state = update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(0.0, 5.0),
+            &[&platform],
+        );
+
+        assert_eq!(location.y, 125.0);
+        assert_eq!(state, EntityState::Standing);
+        assert!(physics_system.get_contacts().bottom);
+        assert_eq!(physics_system.get_velocity().y, 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_running_into_a_wall_on_the_right() {
+        let mut entity_builder = EntityBuilder::new();
+        let wall = create_platform(&mut entity_builder, 100.0, 100.0);
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut location = Vector2::new(48.0, 100.0);
+        update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(5.0, 0.0),
+            &[&wall],
+        );
+
+        assert_eq!(location.x, 50.0);
+        let contacts = physics_system.get_contacts();
+        assert!(contacts.right);
+        assert!(!contacts.left && !contacts.top && !contacts.bottom);
+        assert_eq!(physics_system.get_velocity().x, 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_running_into_a_wall_on_the_left() {
+        let mut entity_builder = EntityBuilder::new();
+        let wall = create_platform(&mut entity_builder, 100.0, 100.0);
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut location = Vector2::new(152.0, 100.0);
+        update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(-5.0, 0.0),
+            &[&wall],
+        );
+
+        assert_eq!(location.x, 150.0);
+        assert!(physics_system.get_contacts().left);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_sliding_down_a_wall_keeps_falling() {
+        let mut entity_builder = EntityBuilder::new();
+        let wall = create_platform(&mut entity_builder, 100.0, 100.0);
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut location = Vector2::new(48.0, 100.0);
+        let state = update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(5.0, 3.0),
+            &[&wall],
+        );
+
+        assert_eq!(location, Vector2::new(50.0, 103.0));
+        assert_eq!(state, EntityState::Falling);
+        assert_eq!(physics_system.get_velocity().y, 3.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_bonking_head_on_a_ceiling() {
+        let mut entity_builder = EntityBuilder::new();
+        let ceiling = create_platform(&mut entity_builder, 100.0, 0.0);
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut location = Vector2::new(100.0, 78.0);
+        let state = update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(0.0, -5.0),
+            &[&ceiling],
+        );
+
+        assert_eq!(location.y, 75.0);
+        assert!(physics_system.get_contacts().top);
+        assert_eq!(physics_system.get_velocity().y, 0.0);
+        assert_eq!(state, EntityState::Falling);
+    }
+
+    #[test]
+    fn ci_test_running_across_a_floor_made_of_tiles() {
+        let mut entity_builder = EntityBuilder::new();
+        let left_tile = create_platform(&mut entity_builder, 25.0, 200.0);
+        let right_tile = create_platform(&mut entity_builder, 75.0, 200.0);
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut location = Vector2::new(30.0, 125.0);
+        let state = update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(5.0, 0.5),
+            &[&left_tile, &right_tile],
+        );
+
+        assert_eq!(location, Vector2::new(35.0, 125.0));
+        assert_eq!(state, EntityState::Standing);
+        let contacts = physics_system.get_contacts();
+        assert!(contacts.bottom && !contacts.left && !contacts.right);
+    }
+
+    #[test]
+    fn ci_test_walking_off_a_ledge_starts_falling() {
+        let mut entity_builder = EntityBuilder::new();
+        let platform = create_platform(&mut entity_builder, 100.0, 200.0);
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut location = Vector2::new(200.0, 125.0);
+        let state = update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(0.0, 0.5),
+            &[&platform],
+        );
+
+        assert_eq!(state, EntityState::Falling);
+        assert!(!physics_system.get_contacts().any());
+    }
+
+    #[test]
+    fn ci_test_non_collidable_entities_are_ignored() {
+        let mut entity_builder = EntityBuilder::new();
+        let decoration = entity_builder
+            .create_entity()
+            .location(100.0, 200.0)
+            .size(50.0, 50.0)
+            .build();
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut location = Vector2::new(100.0, 124.0);
+        let state = update_with_velocity(
+            &mut physics_system,
+            &mut location,
+            Vector2::new(0.0, 5.0),
+            &[&decoration],
+        );
+
+        assert_eq!(state, EntityState::Falling);
+        assert_eq!(location, Vector2::new(100.0, 129.0));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_running_accelerates_up_to_the_max_speed() {
+        let config = Config::default();
+        let mut physics_system = PlayerPhysicsSystem::new(&config);
+        let mut input = InputState::new();
+        input.press(Action::MoveRight);
+        let mut location = Vector2::new(0.0, 0.0);
+        let mut state = EntityState::Standing;
+
+        physics_system.handle_input(&input);
+        physics_system.update(&mut location, 50.0, 100.0, &[], &mut state);
+        assert_eq!(physics_system.get_velocity().x, config.player_run_force);
+
+        for _ in 0..100 {
+            physics_system.handle_input(&input);
+            physics_system.update(&mut location, 50.0, 100.0, &[], &mut state);
+        }
+        assert_eq!(physics_system.get_velocity().x, config.player_max_run_speed);
+    }
 
-        others.iter().for_each(|other| {
-            if !other.collidable {
-                return;
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_friction_stops_running_when_input_is_released() {
+        let mut entity_builder = EntityBuilder::new();
+        let floor = entity_builder
+            .create_entity()
+            .location(0.0, 75.0)
+            .size(5000.0, 50.0)
+            .collidable()
+            .build();
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let bindings = InputBindings::default();
+        let mut input = InputState::new();
+        let mut location = Vector2::new(0.0, 0.0);
+        let mut state = EntityState::Standing;
+        input.key_down(&bindings, ggez::event::KeyCode::Left);
+        for _ in 0..10 {
+            physics_system.handle_input(&input);
+            physics_system.apply_force(&Vector2::new(0.0, 0.5));
+            physics_system.update(&mut location, 50.0, 100.0, &[&floor], &mut state);
+        }
+        assert!(physics_system.get_velocity().x < 0.0);
+
+        input.key_up(ggez::event::KeyCode::Left);
+        for _ in 0..100 {
+            physics_system.handle_input(&input);
+            physics_system.apply_force(&Vector2::new(0.0, 0.5));
+            physics_system.update(&mut location, 50.0, 100.0, &[&floor], &mut state);
+        }
+        assert_eq!(physics_system.get_velocity().x, 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_crouching_does_not_run() {
+        let mut physics_system = PlayerPhysicsSystem::default();
+        let mut input = InputState::new();
+        input.press(Action::MoveRight);
+        input.press(Action::Crouch);
+        let mut location = Vector2::new(0.0, 0.0);
+        let mut state = EntityState::Standing;
+        physics_system.handle_input(&input);
+        physics_system.update(&mut location, 50.0, 100.0, &[], &mut state);
+        assert_eq!(physics_system.get_velocity().x, 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_jumping_only_from_the_ground() {
+        let config = Config::default();
+        let mut physics_system = PlayerPhysicsSystem::new(&config);
+        let mut input = InputState::new();
+        input.press(Action::Jump);
+        let mut location = Vector2::new(0.0, 0.0);
+
+        let mut state = EntityState::Falling;
+        physics_system.handle_input(&input);
+        physics_system.update(&mut location, 50.0, 100.0, &[], &mut state);
+        assert_eq!(physics_system.get_velocity().y, 0.0);
+
+        let mut state = EntityState::Standing;
+        physics_system.handle_input(&input);
+        physics_system.update(&mut location, 50.0, 100.0, &[], &mut state);
+        assert_eq!(physics_system.get_velocity().y, -config.player_jump_force);
+    }
+
+    /// Steps the physics system like the world does, with gravity, input and the floor at y 200
+    struct JumpTest {
+        physics_system: PlayerPhysicsSystem,
+        location: Vector2<f32>,
+        state: EntityState,
+        input: InputState,
+        floor: Entity,
+        config: Config,
+    }
+
+    impl JumpTest {
+        fn new() -> Self {
+            let config = Config::default();
+            let floor = EntityBuilder::new()
+                .create_entity()
+                .location(100.0, 225.0)
+                .size(200.0, 50.0)
+                .collidable()
+                .build();
+            Self {
+                physics_system: PlayerPhysicsSystem::new(&config),
+                location: Vector2::new(100.0, 150.0),
+                state: EntityState::Standing,
+                input: InputState::new(),
+                floor,
+                config,
             }
+        }
+
+        fn step(&mut self) {
+            self.physics_system
+                .apply_force(&Vector2::new(0.0, self.config.gravity_force));
+            self.physics_system.handle_input(&self.input);
+            self.physics_system.update(
+                &mut self.location,
+                50.0,
+                100.0,
+                &[&self.floor],
+                &mut self.state,
+            );
+            self.input.end_tick();
+        }
 
-            if self.overlaps_with(location, width, height, other) {
-                self.velocity *= 0.0;
-                if location.y < other.location.y {
-                    location.y = other.location.y - other.height / 2.0 - height / 2.0;
-                    *state = EntityState::Standing;
+        /// Jump holding the button for the number of ticks and return the highest point reached
+        fn jump_holding_for(&mut self, hold_ticks: u32) -> f32 {
+            let mut highest = self.location.y;
+            self.input.press(Action::Jump);
+            for tick in 0..1000 {
+                if tick == hold_ticks {
+                    self.input.release(Action::Jump);
+                }
+                self.step();
+                highest = highest.min(self.location.y);
+                if tick > 0 && self.state == EntityState::Standing {
+                    break;
                 }
             }
-        })
+            highest
+        }
     }
 
-    fn get_velocity(&self) -> &Vector2<f32> {
-        &self.velocity
+    #[test]
+    fn ci_test_holding_jump_goes_higher_than_tapping() {
+        let tapped_height = JumpTest::new().jump_holding_for(1);
+        let held_height = JumpTest::new().jump_holding_for(1000);
+        let half_held_height = JumpTest::new().jump_holding_for(15);
+        assert!(tapped_height < 150.0);
+        assert!(half_held_height < tapped_height);
+        assert!(held_height < half_held_height);
+    }
+
+    #[test]
+    fn ci_test_jump_states() {
+        let mut test = JumpTest::new();
+        test.input.press(Action::Jump);
+        test.step();
+        assert_eq!(test.state, EntityState::Jumping);
+        test.step();
+        assert_eq!(test.state, EntityState::Jumping);
+
+        test.input.release(Action::Jump);
+        test.step();
+        assert_eq!(test.state, EntityState::Rising);
+
+        let mut falling = false;
+        for _ in 0..1000 {
+            test.step();
+            if test.state == EntityState::Falling {
+                falling = true;
+            }
+            if test.state == EntityState::Standing {
+                break;
+            }
+        }
+        assert!(falling);
+        assert_eq!(test.state, EntityState::Standing);
+    }
+
+    #[test]
+    fn ci_test_coyote_time_allows_jumping_just_after_leaving_the_ground() {
+        let mut test = JumpTest::new();
+        test.step();
+        // walk off of the right side of the floor
+        test.location.x = 300.0;
+        for _ in 0..test.config.coyote_time_ticks - 1 {
+            test.step();
+        }
+        assert_eq!(test.state, EntityState::Falling);
+
+        test.input.press(Action::Jump);
+        test.step();
+        assert_eq!(test.state, EntityState::Jumping);
+    }
+
+    #[test]
+    fn ci_test_no_jumping_after_coyote_time() {
+        let mut test = JumpTest::new();
+        test.step();
+        test.location.x = 300.0;
+        for _ in 0..test.config.coyote_time_ticks + 1 {
+            test.step();
+        }
+
+        test.input.press(Action::Jump);
+        test.step();
+        assert_eq!(test.state, EntityState::Falling);
+    }
+
+    #[test]
+    fn ci_test_no_double_jump_with_coyote_time() {
+        let mut test = JumpTest::new();
+        test.input.press(Action::Jump);
+        test.step();
+        test.input.release(Action::Jump);
+        test.step();
+        assert_eq!(test.state, EntityState::Rising);
+
+        test.input.press(Action::Jump);
+        test.step();
+        assert_eq!(test.state, EntityState::Rising);
+    }
+
+    #[test]
+    fn ci_test_jump_buffered_just_before_landing() {
+        let mut test = JumpTest::new();
+        // fall towards the floor, landing takes a few ticks
+        test.location.y = 145.0;
+        test.physics_system.velocity.y = 1.0;
+        test.state = EntityState::Falling;
+        test.input.press(Action::Jump);
+        test.step();
+        test.input.release(Action::Jump);
+        let mut jumped = false;
+        for _ in 0..test.config.jump_buffer_ticks {
+            test.step();
+            if test.physics_system.get_velocity().y < 0.0 {
+                jumped = true;
+            }
+        }
+        assert!(jumped);
+    }
+
+    #[test]
+    fn ci_test_jump_pressed_too_early_is_forgotten() {
+        let mut test = JumpTest::new();
+        test.location.y = 100.0;
+        test.state = EntityState::Falling;
+        test.input.press(Action::Jump);
+        test.step();
+        test.input.release(Action::Jump);
+        for _ in 0..200 {
+            test.step();
+            assert!(test.physics_system.get_velocity().y >= 0.0);
+        }
+        assert_eq!(test.state, EntityState::Standing);
     }
 }
@@ -0,0 +1,60 @@
+pub mod replay_player;
+
+use std::fs;
+use std::path::Path;
+
+use eyre::{eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::input::InputState;
+use crate::level::Level;
+
+/// Everything needed to play a game again exactly the same way: the levels it was played with and the input of
+/// every update tick. The rest of the game comes from the config, so a replay should be played with the same config
+/// that it was recorded with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub levels: Vec<Level>,
+    /// the input for each update tick, in order
+    pub inputs: Vec<InputState>,
+}
+
+impl Replay {
+    /// Start recording a game played with these levels
+    pub fn new(levels: Vec<Level>) -> Self {
+        Self {
+            levels,
+            inputs: vec![],
+        }
+    }
+
+    /// Call once per update tick with the input that the world was updated with
+    pub fn record(&mut self, input: &InputState) {
+        self.inputs.push(input.clone());
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let ron = ron::ser::to_string(self).wrap_err("could not write replay")?;
+        fs::write(path, ron).wrap_err_with(|| format!("could not save replay {}", path.display()))
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read replay {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|error| {
+            eyre!(
+                "error in replay {} at line {}, column {}: {}",
+                path.display(),
+                error.position.line,
+                error.position.col,
+                error.code
+            )
+        })
+    }
+}
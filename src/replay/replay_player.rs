@@ -0,0 +1,94 @@
+use eyre::Result;
+
+use crate::config::Config;
+use crate::input::InputState;
+use crate::world::World;
+
+use super::Replay;
+
+/// Hands out the recorded input one tick at a time
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    replay: Replay,
+    tick: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, tick: 0 }
+    }
+
+    /// A new world with the levels of the replay, ready for the first tick
+    pub fn create_world(&self, config: &Config, camera_size: (f32, f32)) -> Result<World> {
+        World::from_levels(config, self.replay.levels.clone(), camera_size)
+    }
+
+    /// The input for the next tick, or None once the whole replay has been played
+    pub fn next_input(&mut self) -> Option<&InputState> {
+        let input = self.replay.inputs.get(self.tick)?;
+        self.tick += 1;
+        Some(input)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.replay.inputs.len()
+    }
+
+    /// Play the rest of the replay in the world as fast as possible
+    pub fn play_to_end(&mut self, world: &mut World) -> Result<()> {
+        while let Some(input) = self.next_input() {
+            world.update(input)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::input::action::Action;
+    use crate::input::input_script::InputScript;
+
+    use super::*;
+
+    /// Play the script while recording, then play the recording back in a new world and check that every entity
+    /// ends up in exactly the same place
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_replay_reproduces_the_game() {
+        let config = Config::default();
+        let script: InputScript =
+            "0 press MoveRight\n5 release MoveRight\n300 press Jump\n320 release Jump"
+                .parse()
+                .unwrap();
+        let mut world = World::from_config(&config, (1280.0, 720.0)).unwrap();
+        let mut replay = Replay::new(world.levels().to_vec());
+        let mut input = InputState::new();
+        for tick in 0..400 {
+            script.apply(tick, &mut input);
+            replay.record(&input);
+            world.step(1, &mut input).unwrap();
+        }
+
+        let ron = ron::ser::to_string(&replay).unwrap();
+        let replay: Replay = ron::de::from_str(&ron).unwrap();
+        assert_eq!(replay.ticks(), 400);
+        assert!(replay.inputs[0].is_held(Action::MoveRight));
+        let mut player = ReplayPlayer::new(replay);
+        let mut replayed_world = player.create_world(&config, (1280.0, 720.0)).unwrap();
+        player.play_to_end(&mut replayed_world).unwrap();
+        assert!(player.is_finished());
+
+        let locations = |world: &World| -> Vec<(u32, f32, f32)> {
+            world
+                .entities()
+                .iter()
+                .map(|entity| (entity.id, entity.location.x, entity.location.y))
+                .collect()
+        };
+        assert_eq!(locations(&replayed_world), locations(&world));
+        assert_eq!(
+            replayed_world.player().unwrap().get_state(),
+            world.player().unwrap().get_state()
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use ggez::graphics::WHITE;
+use ggez::{Context, GameResult};
+
+use crate::config::Config;
+use crate::input::action::Action;
+use crate::input::InputState;
+
+use super::title_scene::TitleScene;
+use super::{draw_centered_text, Scene, SceneTransition};
+
+/// Shown when the game ends, confirm or pause goes back to the title
+pub struct GameOverScene {
+    config: Config,
+    config_path: Option<PathBuf>,
+    message: String,
+}
+
+impl GameOverScene {
+    pub fn new(config: Config, config_path: Option<PathBuf>, message: &str) -> Self {
+        Self {
+            config,
+            config_path,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl Scene for GameOverScene {
+    fn update(
+        &mut self,
+        _context: &mut Context,
+        input: &InputState,
+    ) -> GameResult<SceneTransition> {
+        if input.was_pressed(Action::Confirm) || input.was_pressed(Action::Pause) {
+            let title = TitleScene::new(self.config.clone(), self.config_path.clone());
+            Ok(SceneTransition::Replace(Box::new(title)))
+        } else {
+            Ok(SceneTransition::None)
+        }
+    }
+
+    fn draw(&mut self, context: &mut Context, _lag: f32) -> GameResult {
+        draw_centered_text(context, "Game Over", 200.0, 64.0, WHITE)?;
+        draw_centered_text(context, &self.message, 290.0, 32.0, WHITE)?;
+        draw_centered_text(
+            context,
+            "Press enter to go back to the title",
+            360.0,
+            24.0,
+            WHITE,
+        )
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::PathBuf;
+
+use ggez::event::KeyCode;
+use ggez::graphics::{self, DrawParam, Text};
+use ggez::nalgebra::Vector2;
+use ggez::{Context, GameError, GameResult};
+
+use crate::config::Config;
+use crate::debug_overlay::DebugOverlay;
+use crate::drawables::Drawables;
+use crate::hot_reload::HotReload;
+use crate::input::action::Action;
+use crate::input::InputState;
+use crate::replay::replay_player::ReplayPlayer;
+use crate::replay::Replay;
+use crate::world::world_event::WorldEvent;
+use crate::world::World;
+
+use super::game_over_scene::GameOverScene;
+use super::pause_scene::PauseScene;
+use super::{Scene, SceneInput, SceneTransition};
+
+/// Playing the game, wraps the world along with everything that records, replays and reloads it
+pub struct GameplayScene {
+    config: Config,
+    config_path: Option<PathBuf>,
+    world: World,
+    drawables: Drawables,
+    /// where the mouse is in virtual coordinates
+    mouse_location: Option<Vector2<f32>>,
+    debug_overlay: DebugOverlay,
+    /// the game being recorded and where to save it
+    recording: Option<(Replay, PathBuf)>,
+    /// input comes from here instead of the player until the replay ends
+    replay_player: Option<ReplayPlayer>,
+    hot_reload: Option<HotReload>,
+    /// the value of every item picked up so far
+    score: i32,
+}
+
+impl GameplayScene {
+    /// Start from the first level. The config path is watched for changes when the config turns hot
+    /// reloading on.
+    pub fn new(
+        context: &mut Context,
+        config: &Config,
+        config_path: Option<PathBuf>,
+    ) -> GameResult<Self> {
+        let camera_size = (config.virtual_width, config.virtual_height);
+        let replay_player = match &config.play_replay_path {
+            Some(path) => Some(ReplayPlayer::new(
+                Replay::from_path(path).map_err(to_game_error)?,
+            )),
+            None => None,
+        };
+        let world = match &replay_player {
+            Some(replay_player) => replay_player.create_world(config, camera_size),
+            None => World::from_config(config, camera_size),
+        }
+        .map_err(to_game_error)?;
+        let recording = config
+            .record_replay_path
+            .clone()
+            .map(|path| (Replay::new(world.levels().to_vec()), path));
+        let mut drawables = Drawables::new(context, config)?;
+        for atlas_path in config.sprite_atlas_paths.iter() {
+            drawables
+                .load_atlas(context, atlas_path)
+                .map_err(to_game_error)?;
+        }
+        drawables
+            .set_tiles(&world.batched_sprites())
+            .map_err(to_game_error)?;
+        let hot_reload = match &config_path {
+            Some(path) if config.hot_reload => Some(HotReload::new(path.clone(), config)),
+            _ => None,
+        };
+
+        Ok(Self {
+            config: config.clone(),
+            config_path,
+            world,
+            drawables,
+            mouse_location: None,
+            debug_overlay: DebugOverlay::new(config.show_debug_overlay),
+            recording,
+            replay_player,
+            hot_reload,
+            score: 0,
+        })
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    /// React to what happened in the world since the last tick
+    fn handle_events(&mut self) -> GameResult<SceneTransition> {
+        let events: Vec<WorldEvent> = self.world.drain_events().collect();
+        let mut level_changed = false;
+        for event in events {
+            match event {
+                WorldEvent::LevelChanged { .. } => level_changed = true,
+                WorldEvent::AllLevelsCompleted => {
+                    let game_over = GameOverScene::new(
+                        self.config.clone(),
+                        self.config_path.clone(),
+                        &format!("You finished every level with a score of {}", self.score),
+                    );
+                    return Ok(SceneTransition::Replace(Box::new(game_over)));
+                }
+                WorldEvent::ItemPickedUp { value, .. } => self.score += value,
+                WorldEvent::Animation { .. } => {}
+            }
+        }
+        if level_changed {
+            self.drawables
+                .set_tiles(&self.world.batched_sprites())
+                .map_err(to_game_error)?;
+        }
+        Ok(SceneTransition::None)
+    }
+}
+
+/// Keep the whole chain of eyre errors, such as the file and the line, when handing them to ggez
+fn to_game_error(error: eyre::Report) -> GameError {
+    GameError::ResourceLoadError(format!("{:#}", error))
+}
+
+impl Scene for GameplayScene {
+    /// Save the game that was recorded, if we were recording
+    fn on_exit(&mut self) {
+        if let Some((replay, path)) = &self.recording {
+            if let Err(error) = replay.save(path) {
+                eprintln!("could not save the replay: {:#}", error);
+            }
+        }
+    }
+
+    fn update(&mut self, context: &mut Context, input: &InputState) -> GameResult<SceneTransition> {
+        let now = ggez::timer::time_since_start(context);
+        if let Some(hot_reload) = &mut self.hot_reload {
+            hot_reload.update(now, &mut self.config, &mut self.world);
+        }
+        if input.was_pressed(Action::Pause) {
+            let pause = PauseScene::new(self.config.clone(), self.config_path.clone());
+            return Ok(SceneTransition::Push(Box::new(pause)));
+        }
+
+        let mut input = input.clone();
+        input.set_mouse_location(
+            self.mouse_location
+                .map(|location| self.world.camera().screen_to_world(location)),
+        );
+        let replayed_input = self
+            .replay_player
+            .as_mut()
+            .and_then(|replay_player| replay_player.next_input().cloned());
+        let input = replayed_input.as_ref().unwrap_or(&input);
+        if let Some((replay, _)) = &mut self.recording {
+            replay.record(input);
+        }
+        self.world.update(input).map_err(to_game_error)?;
+        self.debug_overlay.record_update(now);
+        self.handle_events()
+    }
+
+    fn draw(&mut self, context: &mut Context, lag: f32) -> GameResult {
+        self.world.draw(context, &self.drawables, lag)?;
+        self.debug_overlay.draw(context, &self.world, lag)?;
+        let screen = graphics::screen_coordinates(context);
+        let score = Text::new(format!("Score: {}", self.score));
+        let (score_width, _score_height) = score.dimensions(context);
+        graphics::draw(
+            context,
+            &score,
+            DrawParam::new().dest([screen.right() - score_width as f32 - 10.0, screen.y + 10.0]),
+        )?;
+        if let Some(hot_reload) = &self.hot_reload {
+            hot_reload.draw(context)?;
+        }
+        Ok(())
+    }
+
+    fn input(&mut self, event: &SceneInput) {
+        match *event {
+            SceneInput::KeyDown {
+                keycode: KeyCode::F3,
+                repeat: false,
+            } => self.debug_overlay.toggle(),
+            SceneInput::KeyDown { .. } => {}
+            SceneInput::MouseMoved(location) => self.mouse_location = Some(location),
+            SceneInput::Resized { width, height } => {
+                self.world.camera_mut().set_size(width, height);
+            }
+        }
+    }
+
+    fn target_update_fps(&self) -> Option<u32> {
+        Some(self.config.target_update_fps)
+    }
+}
@@ -0,0 +1,90 @@
+pub mod game_over_scene;
+pub mod gameplay_scene;
+pub mod pause_scene;
+pub mod scene_stack;
+pub mod title_scene;
+
+use ggez::graphics::{self, Color, DrawParam, Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::nalgebra::Vector2;
+use ggez::{Context, GameResult};
+
+use crate::input::InputState;
+
+/// One screen of the game, such as the title, the game itself or the pause menu. Scenes live on a
+/// [`scene_stack::SceneStack`] and only the one on top is updated.
+pub trait Scene {
+    /// Called when the scene is put on the stack
+    fn on_enter(&mut self) {}
+
+    /// Called when the scene is taken off the stack, including when the game quits
+    fn on_exit(&mut self) {}
+
+    /// One fixed update tick, return a transition to change which scenes are on the stack
+    fn update(&mut self, context: &mut Context, input: &InputState) -> GameResult<SceneTransition>;
+
+    /// Draw in virtual coordinates, lag is how far we are between the last update tick and the next one
+    fn draw(&mut self, context: &mut Context, lag: f32) -> GameResult;
+
+    /// Window events that are not actions, those come through the input state in update
+    fn input(&mut self, _event: &SceneInput) {}
+
+    /// Draw the scene below this one first, like the game behind the pause menu
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// The scene wants the fixed update to run this many times a second
+    fn target_update_fps(&self) -> Option<u32> {
+        None
+    }
+}
+
+pub enum SceneTransition {
+    /// Stay on this scene
+    None,
+    /// Put a scene on top of this one, this one stops updating until it is on top again
+    Push(Box<dyn Scene>),
+    /// Go back to the scene below this one
+    Pop,
+    /// Swap this scene for another one
+    Replace(Box<dyn Scene>),
+    /// Take every scene off the stack and start over with this one
+    ReplaceAll(Box<dyn Scene>),
+    /// Take every scene off the stack, which ends the game
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneInput {
+    KeyDown {
+        keycode: KeyCode,
+        repeat: bool,
+    },
+    /// where the mouse is in virtual coordinates
+    MouseMoved(Vector2<f32>),
+    /// the logical size of the window changed, every scene on the stack is told
+    Resized {
+        width: f32,
+        height: f32,
+    },
+}
+
+/// Draw a line of text centered horizontally on the screen
+fn draw_centered_text(
+    context: &mut Context,
+    text: &str,
+    y: f32,
+    size: f32,
+    color: Color,
+) -> GameResult {
+    let text = Text::new(TextFragment::new(text).scale(Scale::uniform(size)));
+    let (width, _height) = text.dimensions(context);
+    let screen = graphics::screen_coordinates(context);
+    let x = screen.x + (screen.w - width as f32) / 2.0;
+    graphics::draw(
+        context,
+        &text,
+        DrawParam::new().dest([x, screen.y + y]).color(color),
+    )
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, WHITE};
+use ggez::{Context, GameResult};
+
+use crate::config::Config;
+use crate::input::action::Action;
+use crate::input::InputState;
+
+use super::title_scene::TitleScene;
+use super::{draw_centered_text, Scene, SceneTransition};
+
+const BACKDROP_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+
+/// Sits on top of the game so it keeps being drawn without being updated. Pause again to go back to the
+/// game, confirm to leave it for the title.
+pub struct PauseScene {
+    config: Config,
+    config_path: Option<PathBuf>,
+}
+
+impl PauseScene {
+    pub fn new(config: Config, config_path: Option<PathBuf>) -> Self {
+        Self {
+            config,
+            config_path,
+        }
+    }
+}
+
+impl Scene for PauseScene {
+    fn update(
+        &mut self,
+        _context: &mut Context,
+        input: &InputState,
+    ) -> GameResult<SceneTransition> {
+        if input.was_pressed(Action::Pause) {
+            Ok(SceneTransition::Pop)
+        } else if input.was_pressed(Action::Confirm) {
+            let title = TitleScene::new(self.config.clone(), self.config_path.clone());
+            Ok(SceneTransition::ReplaceAll(Box::new(title)))
+        } else {
+            Ok(SceneTransition::None)
+        }
+    }
+
+    fn draw(&mut self, context: &mut Context, _lag: f32) -> GameResult {
+        let screen = graphics::screen_coordinates(context);
+        let backdrop = Mesh::new_rectangle(context, DrawMode::fill(), screen, BACKDROP_COLOR)?;
+        graphics::draw(context, &backdrop, DrawParam::new())?;
+        draw_centered_text(context, "Paused", 200.0, 64.0, WHITE)?;
+        draw_centered_text(
+            context,
+            "Press escape to keep playing, enter to quit to the title",
+            320.0,
+            24.0,
+            WHITE,
+        )
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
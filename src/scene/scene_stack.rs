@@ -0,0 +1,270 @@
+use ggez::{Context, GameResult};
+
+use crate::input::InputState;
+
+use super::{Scene, SceneInput, SceneTransition};
+
+/// The scenes that are open, the last one is on top. Only the top scene is updated and gets input, the
+/// scenes under it are drawn as long as every scene above them is an overlay.
+#[derive(Default)]
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    /// the last size the window was resized to, new scenes are told about it when they are pushed
+    size: Option<(f32, f32)>,
+}
+
+impl SceneStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, mut scene: Box<dyn Scene>) {
+        if let Some((width, height)) = self.size {
+            scene.input(&SceneInput::Resized { width, height });
+        }
+        scene.on_enter();
+        self.scenes.push(scene);
+    }
+
+    pub fn pop(&mut self) -> Option<Box<dyn Scene>> {
+        let mut scene = self.scenes.pop()?;
+        scene.on_exit();
+        Some(scene)
+    }
+
+    /// Take every scene off the stack, top first
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    /// The game ends when there are no scenes left
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    pub fn apply(&mut self, transition: SceneTransition) {
+        match transition {
+            SceneTransition::None => {}
+            SceneTransition::Push(scene) => self.push(scene),
+            SceneTransition::Pop => {
+                self.pop();
+            }
+            SceneTransition::Replace(scene) => {
+                self.pop();
+                self.push(scene);
+            }
+            SceneTransition::ReplaceAll(scene) => {
+                self.clear();
+                self.push(scene);
+            }
+            SceneTransition::Quit => self.clear(),
+        }
+    }
+
+    /// Run one fixed update tick of the top scene
+    pub fn update(&mut self, context: &mut Context, input: &InputState) -> GameResult {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.update(context, input)?,
+            None => SceneTransition::None,
+        };
+        self.apply(transition);
+        Ok(())
+    }
+
+    pub fn draw(&mut self, context: &mut Context, lag: f32) -> GameResult {
+        let first_visible = self.first_visible();
+        for scene in self.scenes[first_visible..].iter_mut() {
+            scene.draw(context, lag)?;
+        }
+        Ok(())
+    }
+
+    /// Resizing reaches every scene so the ones underneath are ready when they are on top again, everything
+    /// else only reaches the top scene
+    pub fn input(&mut self, event: &SceneInput) {
+        match event {
+            SceneInput::Resized { width, height } => {
+                self.size = Some((*width, *height));
+                for scene in self.scenes.iter_mut() {
+                    scene.input(event);
+                }
+            }
+            _ => {
+                if let Some(scene) = self.scenes.last_mut() {
+                    scene.input(event);
+                }
+            }
+        }
+    }
+
+    /// The update rate wanted by the highest scene that has one
+    pub fn target_update_fps(&self) -> Option<u32> {
+        self.scenes
+            .iter()
+            .rev()
+            .find_map(|scene| scene.target_update_fps())
+    }
+
+    fn first_visible(&self) -> usize {
+        self.scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// Writes down when it enters and exits the stack
+    struct TestScene {
+        name: &'static str,
+        overlay: bool,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl TestScene {
+        fn boxed(
+            name: &'static str,
+            overlay: bool,
+            log: &Rc<RefCell<Vec<String>>>,
+        ) -> Box<dyn Scene> {
+            Box::new(Self {
+                name,
+                overlay,
+                log: Rc::clone(log),
+            })
+        }
+    }
+
+    impl Scene for TestScene {
+        fn on_enter(&mut self) {
+            self.log.borrow_mut().push(format!("enter {}", self.name));
+        }
+
+        fn on_exit(&mut self) {
+            self.log.borrow_mut().push(format!("exit {}", self.name));
+        }
+
+        fn update(
+            &mut self,
+            _context: &mut Context,
+            _input: &InputState,
+        ) -> GameResult<SceneTransition> {
+            Ok(SceneTransition::None)
+        }
+
+        fn draw(&mut self, _context: &mut Context, _lag: f32) -> GameResult {
+            Ok(())
+        }
+
+        fn input(&mut self, event: &SceneInput) {
+            if let SceneInput::Resized { width, height } = event {
+                self.log
+                    .borrow_mut()
+                    .push(format!("resize {} {}x{}", self.name, width, height));
+            }
+        }
+
+        fn is_overlay(&self) -> bool {
+            self.overlay
+        }
+
+        fn target_update_fps(&self) -> Option<u32> {
+            if self.name == "gameplay" {
+                Some(60)
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn ci_test_scene_stack_transitions_call_enter_and_exit() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut scenes = SceneStack::new();
+        scenes.apply(SceneTransition::Push(TestScene::boxed(
+            "title", false, &log,
+        )));
+        scenes.apply(SceneTransition::Replace(TestScene::boxed(
+            "gameplay", false, &log,
+        )));
+        scenes.apply(SceneTransition::Push(TestScene::boxed("pause", true, &log)));
+        assert_eq!(scenes.len(), 2);
+        scenes.apply(SceneTransition::Pop);
+        scenes.apply(SceneTransition::None);
+        assert_eq!(scenes.len(), 1);
+        scenes.apply(SceneTransition::Quit);
+        assert!(scenes.is_empty());
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "enter title",
+                "exit title",
+                "enter gameplay",
+                "enter pause",
+                "exit pause",
+                "exit gameplay"
+            ]
+        );
+    }
+
+    #[test]
+    fn ci_test_replace_all_exits_every_scene_from_the_top() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut scenes = SceneStack::new();
+        scenes.push(TestScene::boxed("gameplay", false, &log));
+        scenes.push(TestScene::boxed("pause", true, &log));
+        log.borrow_mut().clear();
+        scenes.apply(SceneTransition::ReplaceAll(TestScene::boxed(
+            "title", false, &log,
+        )));
+        assert_eq!(scenes.len(), 1);
+        assert_eq!(
+            *log.borrow(),
+            vec!["exit pause", "exit gameplay", "enter title"]
+        );
+    }
+
+    #[test]
+    fn ci_test_overlays_show_the_scenes_below_them() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut scenes = SceneStack::new();
+        assert_eq!(scenes.first_visible(), 0);
+        scenes.push(TestScene::boxed("title", false, &log));
+        scenes.push(TestScene::boxed("gameplay", false, &log));
+        assert_eq!(scenes.first_visible(), 1);
+        scenes.push(TestScene::boxed("pause", true, &log));
+        assert_eq!(scenes.first_visible(), 1);
+        assert_eq!(scenes.target_update_fps(), Some(60));
+    }
+
+    #[test]
+    fn ci_test_pushed_scenes_are_told_the_window_size() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut scenes = SceneStack::new();
+        scenes.push(TestScene::boxed("title", false, &log));
+        scenes.input(&SceneInput::Resized {
+            width: 1280.0,
+            height: 720.0,
+        });
+        scenes.push(TestScene::boxed("pause", true, &log));
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "enter title",
+                "resize title 1280x720",
+                "resize pause 1280x720",
+                "enter pause"
+            ]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use ggez::graphics::WHITE;
+use ggez::{Context, GameResult};
+
+use crate::config::Config;
+use crate::input::action::Action;
+use crate::input::InputState;
+
+use super::gameplay_scene::GameplayScene;
+use super::{draw_centered_text, Scene, SceneTransition};
+
+/// The first thing the player sees, confirm starts the game and pause quits
+pub struct TitleScene {
+    config: Config,
+    config_path: Option<PathBuf>,
+}
+
+impl TitleScene {
+    /// The config path is watched for changes once the game starts, if hot reloading is on
+    pub fn new(config: Config, config_path: Option<PathBuf>) -> Self {
+        Self {
+            config,
+            config_path,
+        }
+    }
+}
+
+impl Scene for TitleScene {
+    fn update(&mut self, context: &mut Context, input: &InputState) -> GameResult<SceneTransition> {
+        if input.was_pressed(Action::Confirm) {
+            let gameplay = GameplayScene::new(context, &self.config, self.config_path.clone())?;
+            Ok(SceneTransition::Replace(Box::new(gameplay)))
+        } else if input.was_pressed(Action::Pause) {
+            Ok(SceneTransition::Quit)
+        } else {
+            Ok(SceneTransition::None)
+        }
+    }
+
+    fn draw(&mut self, context: &mut Context, _lag: f32) -> GameResult {
+        draw_centered_text(context, "Platform Template", 200.0, 64.0, WHITE)?;
+        draw_centered_text(
+            context,
+            "Press enter to play, escape to quit",
+            320.0,
+            24.0,
+            WHITE,
+        )
+    }
+}
@@ -0,0 +1,186 @@
+pub mod scaling_mode;
+
+use ggez::graphics::Rect;
+use ggez::nalgebra::Vector2;
+
+use self::scaling_mode::ScalingMode;
+
+/// Dividing by the scale maps the window to the virtual resolution, so it never goes down to zero
+const MIN_SCALE: f32 = 0.01;
+
+/// Maps the virtual resolution that the game is drawn at onto the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    virtual_width: f32,
+    virtual_height: f32,
+    scaling_mode: ScalingMode,
+    scale: f32,
+    /// the part of the virtual space that the window shows, including any bars
+    screen_coordinates: Rect,
+}
+
+impl Viewport {
+    pub fn new(virtual_width: f32, virtual_height: f32, scaling_mode: ScalingMode) -> Self {
+        let mut viewport = Self {
+            virtual_width,
+            virtual_height,
+            scaling_mode,
+            scale: 1.0,
+            screen_coordinates: Rect::new(0.0, 0.0, virtual_width, virtual_height),
+        };
+        viewport.resize(virtual_width, virtual_height);
+        viewport
+    }
+
+    /// Work out the scale and screen coordinates for the new size of the window. A minimised window has no size,
+    /// so the viewport stays as it was until the window comes back.
+    pub fn resize(&mut self, window_width: f32, window_height: f32) {
+        if window_width <= 0.0 || window_height <= 0.0 {
+            return;
+        }
+        let fit = (window_width / self.virtual_width).min(window_height / self.virtual_height);
+        self.scale = match self.scaling_mode {
+            ScalingMode::PixelPerfect => fit.floor().max(1.0),
+            ScalingMode::Letterbox | ScalingMode::Expand => fit.max(MIN_SCALE),
+        };
+
+        let width = window_width / self.scale;
+        let height = window_height / self.scale;
+        self.screen_coordinates = match self.scaling_mode {
+            // the extra space goes evenly on both sides so that the game stays centered
+            ScalingMode::Letterbox | ScalingMode::PixelPerfect => Rect::new(
+                (self.virtual_width - width) / 2.0,
+                (self.virtual_height - height) / 2.0,
+                width,
+                height,
+            ),
+            ScalingMode::Expand => Rect::new(0.0, 0.0, width, height),
+        };
+    }
+
+    /// What to hand to `ggez::graphics::set_screen_coordinates`
+    pub fn screen_coordinates(&self) -> Rect {
+        self.screen_coordinates
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// How much of the world the camera should show, this only grows past the virtual size when expanding
+    pub fn logical_size(&self) -> (f32, f32) {
+        match self.scaling_mode {
+            ScalingMode::Expand => (self.screen_coordinates.w, self.screen_coordinates.h),
+            ScalingMode::Letterbox | ScalingMode::PixelPerfect => {
+                (self.virtual_width, self.virtual_height)
+            }
+        }
+    }
+
+    /// The parts of the window outside of the virtual resolution, which are covered up after drawing
+    pub fn bars(&self) -> Vec<Rect> {
+        let (width, height) = self.logical_size();
+        let screen = self.screen_coordinates;
+        let bars = [
+            Rect::new(screen.x, screen.y, screen.w, -screen.y),
+            Rect::new(screen.x, height, screen.w, screen.bottom() - height),
+            Rect::new(screen.x, 0.0, -screen.x, height),
+            Rect::new(width, 0.0, screen.right() - width, height),
+        ];
+        bars.iter()
+            .filter(|bar| bar.w > 0.0 && bar.h > 0.0)
+            .copied()
+            .collect()
+    }
+
+    /// Turn a location in the window, such as the mouse, into a location in the virtual resolution
+    pub fn window_to_virtual(&self, x: f32, y: f32) -> Vector2<f32> {
+        Vector2::new(
+            self.screen_coordinates.x + x / self.scale,
+            self.screen_coordinates.y + y / self.scale,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_letterbox_keeps_the_virtual_size() {
+        let mut viewport = Viewport::new(320.0, 180.0, ScalingMode::Letterbox);
+        viewport.resize(800.0, 600.0);
+        assert_eq!(viewport.scale(), 2.5);
+        assert_eq!(
+            viewport.screen_coordinates(),
+            Rect::new(0.0, -30.0, 320.0, 240.0)
+        );
+        assert_eq!(viewport.logical_size(), (320.0, 180.0));
+        assert_eq!(
+            viewport.bars(),
+            vec![
+                Rect::new(0.0, -30.0, 320.0, 30.0),
+                Rect::new(0.0, 180.0, 320.0, 30.0)
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_pixel_perfect_only_scales_by_whole_numbers() {
+        let mut viewport = Viewport::new(320.0, 180.0, ScalingMode::PixelPerfect);
+        viewport.resize(990.0, 600.0);
+        assert_eq!(viewport.scale(), 3.0);
+        assert_eq!(
+            viewport.screen_coordinates(),
+            Rect::new(-5.0, -10.0, 330.0, 200.0)
+        );
+        assert_eq!(viewport.bars().len(), 4);
+
+        viewport.resize(100.0, 100.0);
+        assert_eq!(viewport.scale(), 1.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_expand_shows_more_of_the_world() {
+        let mut viewport = Viewport::new(320.0, 180.0, ScalingMode::Expand);
+        viewport.resize(800.0, 600.0);
+        assert_eq!(viewport.scale(), 2.5);
+        assert_eq!(viewport.logical_size(), (320.0, 240.0));
+        assert!(viewport.bars().is_empty());
+    }
+
+    #[test]
+    fn ci_test_window_to_virtual() {
+        let mut viewport = Viewport::new(320.0, 180.0, ScalingMode::Letterbox);
+        viewport.resize(800.0, 600.0);
+        assert_eq!(
+            viewport.window_to_virtual(0.0, 75.0),
+            Vector2::new(0.0, 0.0)
+        );
+        assert_eq!(
+            viewport.window_to_virtual(800.0, 525.0),
+            Vector2::new(320.0, 180.0)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_a_window_without_a_size_keeps_the_viewport() {
+        let mut viewport = Viewport::new(320.0, 180.0, ScalingMode::Letterbox);
+        viewport.resize(800.0, 600.0);
+        let before = viewport;
+        viewport.resize(0.0, 0.0);
+        assert_eq!(viewport, before);
+        viewport.resize(800.0, 0.0);
+        assert_eq!(viewport, before);
+
+        let mut viewport = Viewport::new(320.0, 180.0, ScalingMode::Expand);
+        viewport.resize(1.0, 0.001);
+        assert_eq!(viewport.scale(), MIN_SCALE);
+        let location = viewport.window_to_virtual(1.0, 1.0);
+        assert!(location.x.is_finite() && location.y.is_finite());
+    }
+}
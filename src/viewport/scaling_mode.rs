@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// How the virtual resolution is fit into a window of a different size
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ScalingMode {
+    /// Scale as much as fits and fill the rest of the window with bars
+    #[default]
+    Letterbox,
+    /// Like letterbox but only by whole numbers, so that every pixel stays the same size
+    PixelPerfect,
+    /// Scale as much as fits and show more of the world to fill the rest of the window
+    Expand,
+}
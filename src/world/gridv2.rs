@@ -5,7 +5,7 @@ use eyre::Result;
 use ggez::graphics::Rect;
 use ggez::nalgebra::Vector2;
 
-use crate::{entity, Entity};
+use crate::Entity;
 
 pub struct Grid {
     width: u32,
@@ -35,56 +35,167 @@ impl Grid {
         }
     }
 
+    /// Insert the entity id into every cell that the bounding box of the entity touches.
+    /// Entities that are partially or completely outside of the world are clamped to the edge cells.
     pub fn insert(&mut self, entity: &Entity) -> Result<()> {
-        let coordinates = self.get_coordinates(entity.location.x, entity.location.y);
+        for coordinates in
+            self.get_covered_coordinates(&entity.location, entity.width, entity.height)
+        {
+            let cell = self.cells.get_mut(&coordinates).ok_or_else(|| {
+                eyre!(
+                    "error inserting entity into grid at coordinates: {:?}",
+                    coordinates
+                )
+            })?;
 
-        let cell = self.cells.get_mut(&coordinates).ok_or_else(|| {
-            eyre!(
-                "error inserting entity into grid at coordinates: {:?}",
-                coordinates
-            )
-        })?;
-
-        cell.push(entity.id);
+            if !cell.contains(&entity.id) {
+                cell.push(entity.id);
+            }
+        }
 
         Ok(())
     }
 
-    /// Get the ids of the entities that are within the query rectangle
+    /// Remove the entity id from every cell that the entity currently covers
+    pub fn remove(&mut self, entity: &Entity) {
+        let covered = self.get_covered_coordinates(&entity.location, entity.width, entity.height);
+        self.remove_id_from_cells(entity.id, &covered);
+    }
+
+    /// Get the ids of the entities that are within the query rectangle.
+    /// Entities covering more than one cell are only returned once.
     pub fn query(&self, query: Rect) -> Vec<&u32> {
         let mut found_ids = vec![];
         let start = self.get_coordinates(query.x, query.y);
         let end = self.get_coordinates(query.x + query.w, query.y + query.h);
 
-        let mut indexes = vec![];
         for x in start.0..=end.0 {
             for y in start.1..=end.1 {
-                indexes.push((x, y));
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    cell.iter().for_each(|id| found_ids.push(id));
+                }
             }
         }
 
-        indexes.iter().for_each(|index| {
-            if let Some(cell) = self.cells.get(index) {
-                cell.iter().for_each(|id| found_ids.push(id));
-            }
-        });
-
+        found_ids.sort();
+        found_ids.dedup();
         found_ids
     }
 
+    /// Broad phase query for the ids of entities that are within the margin around the bounding box.
+    /// The margin should be at least as large as the distance an entity can move in one update.
+    pub fn query_near(&self, bounding_box: Rect, (margin_x, margin_y): (f32, f32)) -> Vec<&u32> {
+        self.query(Rect::new(
+            bounding_box.x - margin_x,
+            bounding_box.y - margin_y,
+            bounding_box.w + margin_x * 2.0,
+            bounding_box.h + margin_y * 2.0,
+        ))
+    }
+
+    /// The cells within the area that have at least one entity in them, for seeing the grid while debugging
+    pub fn occupied_cells(&self, area: Rect) -> Vec<Rect> {
+        let start = self.get_coordinates(area.x, area.y);
+        let end = self.get_coordinates(area.x + area.w, area.y + area.h);
+        let mut occupied = vec![];
+        for x in start.0..=end.0 {
+            for y in start.1..=end.1 {
+                if self.cells.get(&(x, y)).is_some_and(|cell| !cell.is_empty()) {
+                    occupied.push(Rect::new(
+                        x as f32 * self.unit_width,
+                        y as f32 * self.unit_height,
+                        self.unit_width,
+                        self.unit_height,
+                    ));
+                }
+            }
+        }
+        occupied
+    }
+
+    /// Move the entity id into the cells it covers at its new location. The entity must have the same size
+    /// that it had when it was at the old location.
     pub fn update_entity_location(&mut self, old_location: Vector2<f32>, entity: &Entity) {
-        let old_coordinates = self.get_coordinates(old_location.x, old_location.y);
-        let new_coordinates = self.get_coordinates(entity.location.x, entity.location.y);
-        if old_coordinates != new_coordinates {
-            // remove the entity id from the old cell
-            // add entity id to new cell
+        self.update_entity_bounds(old_location, (entity.width, entity.height), entity);
+    }
+
+    /// Move the entity id into the cells it covers now that its location or size changed
+    pub fn update_entity_bounds(
+        &mut self,
+        old_location: Vector2<f32>,
+        (old_width, old_height): (f32, f32),
+        entity: &Entity,
+    ) {
+        let old_coordinates = self.get_covered_coordinates(&old_location, old_width, old_height);
+        let new_coordinates =
+            self.get_covered_coordinates(&entity.location, entity.width, entity.height);
+        if old_coordinates == new_coordinates {
+            return;
+        }
+
+        let left_behind: Vec<(u32, u32)> = old_coordinates
+            .iter()
+            .filter(|coordinates| !new_coordinates.contains(coordinates))
+            .copied()
+            .collect();
+        self.remove_id_from_cells(entity.id, &left_behind);
+
+        for coordinates in new_coordinates {
+            if let Some(cell) = self.cells.get_mut(&coordinates) {
+                if !cell.contains(&entity.id) {
+                    cell.push(entity.id);
+                }
+            }
+        }
+    }
+
+    fn remove_id_from_cells(&mut self, id: u32, coordinates: &[(u32, u32)]) {
+        coordinates.iter().for_each(|coordinates| {
+            if let Some(cell) = self.cells.get_mut(coordinates) {
+                cell.retain(|cell_id| *cell_id != id);
+            }
+        });
+    }
+
+    /// All of the cell coordinates that a box centered on the location touches
+    fn get_covered_coordinates(
+        &self,
+        location: &Vector2<f32>,
+        width: f32,
+        height: f32,
+    ) -> Vec<(u32, u32)> {
+        let left = location.x - width / 2.0;
+        let top = location.y - height / 2.0;
+        let start = self.get_coordinates(left, top);
+        // a box that ends exactly on a cell border does not reach into the next cell
+        let end_x = self
+            .clamp_index(((left + width) / self.unit_width).ceil() - 1.0, self.width)
+            .max(start.0);
+        let end_y = self
+            .clamp_index(
+                ((top + height) / self.unit_height).ceil() - 1.0,
+                self.height,
+            )
+            .max(start.1);
+
+        let mut coordinates = vec![];
+        for x in start.0..=end_x {
+            for y in start.1..=end_y {
+                coordinates.push((x, y));
+            }
         }
+        coordinates
     }
 
     fn get_coordinates(&self, x: f32, y: f32) -> (u32, u32) {
-        let index_x = if x == 0.0 { 0.0 } else { x / self.unit_width };
-        let index_y = if y == 0.0 { 0.0 } else { y / self.unit_height };
-        (index_x as u32, index_y as u32)
+        (
+            self.clamp_index((x / self.unit_width).floor(), self.width),
+            self.clamp_index((y / self.unit_height).floor(), self.height),
+        )
+    }
+
+    fn clamp_index(&self, index: f32, count: u32) -> u32 {
+        (index.max(0.0) as u32).min(count.saturating_sub(1))
     }
 }
 
@@ -92,9 +203,7 @@ impl Grid {
 mod test {
     use ggez::graphics::Rect;
 
-    use crate::draw_system::player_draw_system::PlayerDrawSystem;
     use crate::entity::builder::EntityBuilder;
-    use crate::physics_system::player_physics_system::PlayerPhysicsSystem;
 
     use super::*;
 
@@ -119,7 +228,7 @@ mod test {
         let mut entity_builder = EntityBuilder::new();
         let entity = entity_builder.create_entity().location(3.0, 3.0).build();
         let entity_id = entity.id;
-        grid.insert(&entity);
+        grid.insert(&entity).unwrap();
         assert_eq!(grid.cells.get(&(0, 0)).unwrap()[0], entity_id);
     }
 
@@ -138,17 +247,214 @@ mod test {
         assert_eq!(visible_entities[0], &id_to_get);
     }
 
+    #[test]
+    fn ci_test_occupied_cells_in_area() {
+        let mut grid = Grid::new(100.0, 100.0, 10.0, 10.0);
+        let mut entity_builder = EntityBuilder::new();
+        let entity = entity_builder
+            .create_entity()
+            .location(20.0, 15.0)
+            .size(10.0, 10.0)
+            .build();
+        grid.insert(&entity).unwrap();
+
+        assert_eq!(
+            grid.occupied_cells(Rect::new(0.0, 0.0, 50.0, 50.0)),
+            vec![
+                Rect::new(10.0, 10.0, 10.0, 10.0),
+                Rect::new(20.0, 10.0, 10.0, 10.0)
+            ]
+        );
+        assert!(grid
+            .occupied_cells(Rect::new(50.0, 50.0, 20.0, 20.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn ci_test_query_near_includes_the_margin() {
+        let mut grid = Grid::new(100.0, 100.0, 10.0, 10.0);
+        let mut entity_builder = EntityBuilder::new();
+        let next_door = entity_builder
+            .create_entity()
+            .location(35.0, 15.0)
+            .size(10.0, 10.0)
+            .build();
+        let far_away = entity_builder
+            .create_entity()
+            .location(85.0, 85.0)
+            .size(10.0, 10.0)
+            .build();
+        grid.insert(&next_door).unwrap();
+        grid.insert(&far_away).unwrap();
+        let bounding_box = Rect::new(10.0, 10.0, 10.0, 10.0);
+
+        assert!(grid.query_near(bounding_box, (0.0, 0.0)).is_empty());
+        assert_eq!(
+            grid.query_near(bounding_box, (10.0, 10.0)),
+            vec![&next_door.id]
+        );
+    }
+
     #[test]
     fn ci_test_move_entity_between_cells_in_grid() {
         let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
         let mut entity_builder = EntityBuilder::new();
         let mut entity = entity_builder.create_entity().location(1.5, 1.5).build();
-        grid.insert(&entity);
+        grid.insert(&entity).unwrap();
         let old_location = entity.location;
         entity.location.x = 2.5;
-        let new_location = entity.location;
         grid.update_entity_location(old_location, &entity);
         let coordinates = grid.get_coordinates(entity.location.x, entity.location.y);
         assert_eq!(grid.cells.get(&coordinates).unwrap().len(), 1);
+        assert!(grid.cells.get(&(0, 0)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ci_test_remove_entity_from_grid() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        let entity = entity_builder
+            .create_entity()
+            .location(4.0, 4.0)
+            .size(3.0, 3.0)
+            .build();
+        grid.insert(&entity).unwrap();
+        grid.remove(&entity);
+        assert!(grid.cells.values().all(|cell| cell.is_empty()));
+        assert!(grid.query(Rect::new(0.0, 0.0, 10.0, 10.0)).is_empty());
+    }
+
+    #[test]
+    fn ci_test_entity_spanning_multiple_cells() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        // covers x 2.5..5.5 and y 1.0..3.0 which is cells x 1..=2 and y 0..=1
+        let entity = entity_builder
+            .create_entity()
+            .location(4.0, 2.0)
+            .size(3.0, 2.0)
+            .build();
+        grid.insert(&entity).unwrap();
+
+        let mut occupied: Vec<(u32, u32)> = grid
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.contains(&entity.id))
+            .map(|(coordinates, _)| *coordinates)
+            .collect();
+        occupied.sort();
+        assert_eq!(occupied, vec![(1, 0), (1, 1), (2, 0), (2, 1)]);
+
+        let found = grid.query(Rect::new(0.0, 0.0, 10.0, 10.0));
+        assert_eq!(found, vec![&entity.id]);
+    }
+
+    #[test]
+    fn ci_test_entity_ending_on_cell_border_does_not_cover_next_cell() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        let entity = entity_builder
+            .create_entity()
+            .location(1.0, 1.0)
+            .size(2.0, 2.0)
+            .build();
+        grid.insert(&entity).unwrap();
+        assert_eq!(grid.cells.get(&(0, 0)).unwrap(), &vec![entity.id]);
+        assert!(grid.cells.get(&(1, 0)).unwrap().is_empty());
+        assert!(grid.cells.get(&(0, 1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ci_test_move_multi_cell_entity_across_cells() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        let mut entity = entity_builder
+            .create_entity()
+            .location(2.0, 1.0)
+            .size(2.0, 1.0)
+            .build();
+        grid.insert(&entity).unwrap();
+        let old_location = entity.location;
+        entity.location.x = 6.5;
+        grid.update_entity_location(old_location, &entity);
+
+        assert!(grid.cells.get(&(0, 0)).unwrap().is_empty());
+        assert!(grid.cells.get(&(1, 0)).unwrap().is_empty());
+        assert_eq!(grid.cells.get(&(2, 0)).unwrap(), &vec![entity.id]);
+        assert_eq!(grid.cells.get(&(3, 0)).unwrap(), &vec![entity.id]);
+        assert!(grid.query(Rect::new(0.0, 0.0, 3.0, 3.0)).is_empty());
+    }
+
+    #[test]
+    fn ci_test_move_entity_within_overlapping_cells_keeps_single_entry() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        let mut entity = entity_builder
+            .create_entity()
+            .location(2.0, 1.0)
+            .size(2.0, 1.0)
+            .build();
+        grid.insert(&entity).unwrap();
+        let old_location = entity.location;
+        entity.location.x = 3.5;
+        grid.update_entity_location(old_location, &entity);
+
+        assert!(grid.cells.get(&(0, 0)).unwrap().is_empty());
+        assert_eq!(grid.cells.get(&(1, 0)).unwrap(), &vec![entity.id]);
+        assert_eq!(grid.cells.get(&(2, 0)).unwrap(), &vec![entity.id]);
+    }
+
+    #[test]
+    fn ci_test_entities_at_world_edges_are_clamped_into_the_grid() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        let top_left = entity_builder
+            .create_entity()
+            .location(-1.0, -3.0)
+            .size(1.0, 1.0)
+            .build();
+        let bottom_right = entity_builder
+            .create_entity()
+            .location(10.0, 10.0)
+            .size(1.0, 1.0)
+            .build();
+        let far_outside = entity_builder.create_entity().location(500.0, 3.0).build();
+        grid.insert(&top_left).unwrap();
+        grid.insert(&bottom_right).unwrap();
+        grid.insert(&far_outside).unwrap();
+
+        assert_eq!(grid.cells.get(&(0, 0)).unwrap(), &vec![top_left.id]);
+        assert_eq!(grid.cells.get(&(4, 4)).unwrap(), &vec![bottom_right.id]);
+        assert_eq!(grid.cells.get(&(4, 1)).unwrap(), &vec![far_outside.id]);
+        assert_eq!(grid.cells.len(), 25);
+    }
+
+    #[test]
+    fn ci_test_move_entity_out_of_the_world_and_back() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        let mut entity = entity_builder.create_entity().location(9.0, 5.0).build();
+        grid.insert(&entity).unwrap();
+
+        let old_location = entity.location;
+        entity.location.x = 15.0;
+        grid.update_entity_location(old_location, &entity);
+        assert_eq!(grid.cells.get(&(4, 2)).unwrap(), &vec![entity.id]);
+
+        let old_location = entity.location;
+        entity.location.x = 1.0;
+        grid.update_entity_location(old_location, &entity);
+        assert!(grid.cells.get(&(4, 2)).unwrap().is_empty());
+        assert_eq!(grid.cells.get(&(0, 2)).unwrap(), &vec![entity.id]);
+    }
+
+    #[test]
+    fn ci_test_query_outside_of_the_world_returns_edge_cells() {
+        let mut grid = Grid::new(10.0, 10.0, 2.0, 2.0);
+        let mut entity_builder = EntityBuilder::new();
+        let entity = entity_builder.create_entity().location(0.5, 0.5).build();
+        grid.insert(&entity).unwrap();
+        let found = grid.query(Rect::new(-640.0, -360.0, 641.0, 361.0));
+        assert_eq!(found, vec![&entity.id]);
     }
 }
@@ -1,19 +1,34 @@
-pub mod cell;
 pub mod gridv2;
+pub mod player_transition;
+pub mod world_event;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use entity::builder::EntityBuilder;
-use entity::entity_type;
-use ggez::graphics::Rect;
+use entity::entity_type::{self, EntityType};
+use eyre::{bail, eyre, Result};
+use ggez::graphics::{apply_transformations, pop_transform, push_transform, Rect};
 use ggez::nalgebra::Vector2;
 use ggez::{Context, GameResult};
 use gridv2::Grid;
+use player_transition::PlayerTransition;
+use world_event::WorldEvent;
 
-use crate::camera::Camera;
-use crate::drawables::Drawables;
+use crate::animation::{AnimationSet, Animator};
+use crate::camera::shake::Shake;
+use crate::camera::{Camera, CameraTarget};
+use crate::config::Config;
+use crate::draw_system::platform_draw_system::PlatformDrawSystem;
+use crate::draw_system::player_draw_system::PlayerDrawSystem;
+use crate::drawables::{Drawables, SpriteDrawing};
+use crate::entity::render_layer::RenderLayer;
 use crate::entity::{self, Entity};
+use crate::input::InputState;
+use crate::level::item_kind::ItemKind;
+use crate::level::parallax_layer::ParallaxLayer;
 use crate::level::Level;
+use crate::physics_system::enemy_physics_system::EnemyPhysicsSystem;
+use crate::physics_system::player_physics_system::PlayerPhysicsSystem;
 
 pub struct World {
     grid: Option<Grid>,
@@ -25,7 +40,19 @@ pub struct World {
     levels: Vec<Level>,
     current_level_index: usize,
     camera: Camera,
-    entities: HashMap<u32, Entity>,
+    /// kept sorted by id so that everything that goes through the entities happens in the same order every time
+    entities: BTreeMap<u32, Entity>,
+    /// ids of the entities with a physics system, the only ones that need updating each tick
+    dynamic_entity_ids: BTreeSet<u32>,
+    entity_builder: EntityBuilder,
+    player_id: Option<u32>,
+    /// where the player starts the game, used when respawning into a level without a player spawn point
+    player_start: Vector2<f32>,
+    player_transition: PlayerTransition,
+    events: Vec<WorldEvent>,
+    all_levels_completed: bool,
+    /// the kind of every item entity in the world by its id
+    items: BTreeMap<u32, ItemKind>,
 }
 
 impl World {
@@ -34,6 +61,89 @@ impl World {
         Self::default()
     }
 
+    /// Create the world described by the config with its levels loaded and the player added. Nothing here needs
+    /// a ggez Context, so a world can be simulated without a window.
+    pub fn from_config(config: &Config, camera_size: (f32, f32)) -> Result<Self> {
+        let levels = config
+            .level_paths
+            .iter()
+            .map(|level_path| {
+                Level::load(
+                    level_path,
+                    config.world_unit_width,
+                    config.world_unit_height,
+                )
+            })
+            .collect::<Result<Vec<Level>>>()?;
+        Self::from_levels(config, levels, camera_size)
+    }
+
+    /// Create the world described by the config with levels that are already loaded, such as the ones saved in a
+    /// replay
+    pub fn from_levels(
+        config: &Config,
+        levels: Vec<Level>,
+        (camera_width, camera_height): (f32, f32),
+    ) -> Result<Self> {
+        let mut camera = Camera::new(0.0, 0.0, camera_width, camera_height);
+        camera
+            .set_dead_zone(
+                config.camera_dead_zone_width,
+                config.camera_dead_zone_height,
+            )
+            .set_smoothing(config.camera_smoothing)
+            .set_look_ahead(
+                config.camera_look_ahead_ticks,
+                config.camera_max_look_ahead_x,
+                config.camera_max_look_ahead_y,
+            )
+            .set_shake(Shake::new(
+                config.camera_shake_decay,
+                config.camera_max_shake_x,
+                config.camera_max_shake_y,
+            ));
+        let mut world = World::new();
+        world
+            .set_gravity(config.gravity_force)
+            .set_size(config.world_width, config.world_height)
+            .set_unit_size(config.world_unit_width, config.world_unit_height)
+            .set_camera(camera)
+            .set_player_transition(config.player_level_transition);
+        for level in levels {
+            world.add_level(level);
+        }
+        world.build()?;
+
+        let (player_x, player_y) = match world
+            .current_level()
+            .and_then(|level| level.player_spawn_point())
+        {
+            Some(spawn_point) => (spawn_point.x, spawn_point.y),
+            None => (config.player_start_x, config.player_start_y),
+        };
+        world.set_player_start(player_x, player_y);
+        let mut player_draw_system = PlayerDrawSystem::new(config.player_sprite.clone());
+        if let Some(animations_path) = &config.player_animations_path {
+            let animation_set = AnimationSet::from_path(animations_path)?;
+            player_draw_system = player_draw_system.with_animator(Animator::new(animation_set));
+        }
+        let player = world
+            .entity_builder()
+            .create_entity()
+            .location(player_x, player_y)
+            .size(config.player_width, config.player_height)
+            .draw_system(Box::new(player_draw_system))
+            .affected_by_gravity()
+            .physics_system(Box::new(PlayerPhysicsSystem::new(config)))
+            .player_controlled()
+            .entity_type(EntityType::Player)
+            .build();
+        let player_id = player.id;
+        world.add_entity(player)?;
+        world.camera_follow(player_id);
+        Ok(world)
+    }
+
     pub fn set_gravity(&mut self, gravity: f32) -> &mut Self {
         self.gravity.y = gravity;
 
@@ -52,88 +162,590 @@ impl World {
         self
     }
 
+    /// Where the player goes when respawning into a level that does not have a player spawn point
+    pub fn set_player_start(&mut self, x: f32, y: f32) -> &mut Self {
+        self.player_start = Vector2::new(x, y);
+        self
+    }
+
+    pub fn set_player_transition(&mut self, player_transition: PlayerTransition) -> &mut Self {
+        self.player_transition = player_transition;
+        self
+    }
+
     /// Load the level which is the final step in creating the world. We are now ready to start the game.
-    pub fn build(&mut self, entity_builder: &mut EntityBuilder) {
-        self.load_level(entity_builder);
+    pub fn build(&mut self) -> Result<()> {
+        self.load_level()
+    }
+
+    /// The builder that hands out the entity ids for this world. Entities added to the world should be created
+    /// with it so that their ids are unique.
+    pub fn entity_builder(&mut self) -> &mut EntityBuilder {
+        &mut self.entity_builder
     }
 
-    pub fn add_entity(&mut self, entity: Entity) {
+    /// Take ownership of the entity and index it in the grid. The world needs to be built first.
+    pub fn add_entity(&mut self, entity: Entity) -> Result<()> {
+        let grid = self
+            .grid
+            .as_mut()
+            .ok_or_else(|| eyre!("the world must be built before entities can be added"))?;
+        grid.insert(&entity)?;
+        if entity.physics_system.is_some() {
+            self.dynamic_entity_ids.insert(entity.id);
+        }
+        if entity.is_player_controlled() && self.player_id.is_none() {
+            self.player_id = Some(entity.id);
+        }
+        self.entities.insert(entity.id, entity);
+        Ok(())
+    }
+
+    /// Remove the entity from the world and the grid, handing it back to the caller
+    pub fn remove_entity(&mut self, id: u32) -> Option<Entity> {
+        let entity = self.entities.remove(&id)?;
+        self.dynamic_entity_ids.remove(&id);
+        self.items.remove(&id);
+        if self.player_id == Some(id) {
+            self.player_id = None;
+        }
         if let Some(grid) = &mut self.grid {
-            grid.insert(&entity);
-            self.entities.insert(entity.id, entity);
+            grid.remove(&entity);
         }
+        Some(entity)
     }
 
-    pub fn draw(&self, context: &mut Context, drawables: &Drawables, lag: f32) -> GameResult {
-        // push_transform(context, Some(DrawParam::new().dest(self.dest).to_matrix()));
-        // apply_transformations(context)?;
-        // draw(context, &drawables.grid, DrawParam::new())?;
-        if let Some(grid) = &self.grid {
-            let entities = grid.query(self.camera.as_rect());
+    /// Every entity in the world, sorted by id
+    pub fn entities(&self) -> Vec<&Entity> {
+        self.entities.values().collect()
+    }
 
-            entities
+    pub fn get_entity(&self, id: u32) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn player(&self) -> Option<&Entity> {
+        self.player_id.and_then(|id| self.entities.get(&id))
+    }
+
+    pub fn draw(&self, context: &mut Context, drawables: &Drawables, lag: f32) -> GameResult {
+        push_transform(context, Some(self.camera.transform(lag).to_matrix()));
+        apply_transformations(context)?;
+        self.draw_parallax_layers(context, drawables, lag)?;
+        // entities are drawn behind where they are by lag, so this looks a little further out than the view
+        let mut visible_entities: Vec<&Entity> = self
+            .entities_near(self.camera.view(lag))
+            .into_iter()
+            // these are drawn with the tiles
+            .filter(|entity| !self.is_batched(entity, drawables))
+            .collect();
+        // the ids are sorted so this keeps entities with the same layer and z in the same order every frame
+        visible_entities.sort_by_key(|entity| (entity.layer, entity.z));
+        for layer in RenderLayer::ALL.iter() {
+            let layer_entities: Vec<&Entity> = visible_entities
                 .iter()
-                .try_for_each(|entity| entity.draw(context, drawables, lag))?;
+                .copied()
+                .filter(|entity| entity.layer == *layer)
+                .collect();
+            if *layer != RenderLayer::Tiles {
+                layer_entities
+                    .iter()
+                    .try_for_each(|entity| entity.draw(context, drawables, lag))?;
+                continue;
+            }
+            for part in tile_layer_parts(&drawables.tile_zs(), &layer_entities) {
+                match part {
+                    TileLayerPart::Tiles(z) => drawables.draw_tiles(context, z)?,
+                    TileLayerPart::Entity(entity) => entity.draw(context, drawables, lag)?,
+                }
+            }
+        }
+        pop_transform(context);
+        apply_transformations(context)
+    }
+
+    /// The backgrounds of the current level, behind everything else. Backgrounds without a loaded sprite are skipped.
+    fn draw_parallax_layers(
+        &self,
+        context: &mut Context,
+        drawables: &Drawables,
+        lag: f32,
+    ) -> GameResult {
+        let level = match self.current_level() {
+            Some(level) => level,
+            None => return Ok(()),
+        };
+        let mut parallax_layers: Vec<&ParallaxLayer> = level
+            .parallax_layers
+            .iter()
+            .filter(|parallax_layer| drawables.has_sprite(&parallax_layer.sprite))
+            .collect();
+        parallax_layers.sort_by_key(|parallax_layer| parallax_layer.z);
+        let camera_location = self.camera.drawn_location(lag);
+        let view = self.camera.view(lag);
+        for parallax_layer in parallax_layers {
+            for position in parallax_layer.positions(camera_location, view) {
+                let size = (parallax_layer.width, parallax_layer.height);
+                let drawing = SpriteDrawing {
+                    center: position + Vector2::new(size.0 / 2.0, size.1 / 2.0),
+                    size,
+                    flip_x: false,
+                    flip_y: false,
+                };
+                drawables.draw_sprite(context, &parallax_layer.sprite, drawing)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The entities in or next to the area, sorted by id
+    pub fn entities_near(&self, area: Rect) -> Vec<&Entity> {
+        match &self.grid {
+            Some(grid) => grid
+                .query_near(area, (self.unit_width, self.unit_height))
+                .into_iter()
+                .filter_map(|id| self.entities.get(id))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// The grid cells within the area that have an entity in them
+    pub fn occupied_grid_cells(&self, area: Rect) -> Vec<Rect> {
+        match &self.grid {
+            Some(grid) => grid.occupied_cells(area),
+            None => vec![],
+        }
+    }
+
+    /// The sprites of the entities in the tile layer that never move, with where they are and their z, for drawing in
+    /// sprite batches
+    pub fn batched_sprites(&self) -> Vec<(String, Rect, i32)> {
+        let mut entities: Vec<&Entity> = self
+            .entities
+            .values()
+            .filter(|entity| !self.dynamic_entity_ids.contains(&entity.id))
+            .filter(|entity| entity.layer == RenderLayer::Tiles)
+            .filter(|entity| entity.batched_sprite().is_some())
+            .collect();
+        entities.sort_by_key(|entity| (entity.z, entity.id));
+        entities
+            .into_iter()
+            .filter_map(|entity| {
+                let sprite = entity.batched_sprite()?;
+                Some((sprite.to_owned(), entity.bounding_box(), entity.z))
+            })
+            .collect()
+    }
+
+    fn is_batched(&self, entity: &Entity, drawables: &Drawables) -> bool {
+        entity.layer == RenderLayer::Tiles
+            && !self.dynamic_entity_ids.contains(&entity.id)
+            && entity
+                .batched_sprite()
+                .is_some_and(|sprite| drawables.has_sprite(sprite))
+    }
+
+    /// Run the given number of update ticks, the same way that the game does between frames. Presses in the input
+    /// only count for the first tick.
+    pub fn step(&mut self, ticks: u32, input: &mut InputState) -> Result<()> {
+        for _ in 0..ticks {
+            self.update(input)?;
+            input.end_tick();
         }
-        // pop_transform(context);
         Ok(())
     }
 
-    pub fn update(&mut self) {
+    /// Move everything that can move by one tick. Entities update in the order of their ids and nothing depends on
+    /// the time or on hash map order, so the same input always gives the same result. Reaching a goal moves on to the next level at the end of the tick.
+    pub fn update(&mut self, input: &InputState) -> Result<()> {
         let gravity = &self.gravity;
+        let margin = (self.unit_width, self.unit_height);
+        let player_id = self.player_id;
+        let entities = &mut self.entities;
+        let events = &mut self.events;
+        let mut reached_goal = false;
+        let mut picked_up = vec![];
+        // everything thinks about where the player was at the start of the tick, whatever order they update in
+        let player_location = player_id
+            .and_then(|id| entities.get(&id))
+            .map(|player| player.location);
         if let Some(grid) = &mut self.grid {
-            let collidable_entities = grid.get_all_cloned();
-            let entities = grid.get_all_entities_mut();
-            let mut moved_entities = vec![];
-            for entity in entities {
-                // get collidable entities near the entity
+            for &id in self.dynamic_entity_ids.iter() {
+                // take the entity out while it updates so that the others can be borrowed
+                let mut entity = match entities.remove(&id) {
+                    Some(entity) => entity,
+                    None => continue,
+                };
+                let nearby_collidables: Vec<&Entity> = grid
+                    .query_near(entity.bounding_box(), margin)
+                    .into_iter()
+                    .filter_map(|nearby_id| entities.get(nearby_id))
+                    .filter(|nearby| nearby.collidable)
+                    .collect();
+
+                entity.think(player_location, &nearby_collidables);
                 let old_location = entity.location;
-                entity.update(gravity, collidable_entities.clone());
-                let new_location = entity.location;
-                if old_location != new_location {
-                    // we need to tell the grid that an entity has been updated and might need to change cells
-                    moved_entities.push((entity.id, old_location, new_location));
+                if entity.update(gravity, &nearby_collidables, input) {
+                    // the entity might be in different cells now
+                    grid.update_entity_location(old_location, &entity);
                 }
+                events.extend(entity.take_animation_events().into_iter().map(|name| {
+                    WorldEvent::Animation {
+                        entity_id: id,
+                        name,
+                    }
+                }));
+                if player_id == Some(id) {
+                    let bounding_box = entity.bounding_box();
+                    let touching: Vec<&Entity> = grid
+                        .query(bounding_box)
+                        .into_iter()
+                        .filter_map(|other_id| entities.get(other_id))
+                        .filter(|other| other.bounding_box().overlaps(&bounding_box))
+                        .collect();
+                    reached_goal |= touching
+                        .iter()
+                        .any(|other| other.entity_type == Some(EntityType::Goal));
+                    picked_up.extend(
+                        touching
+                            .iter()
+                            .filter(|other| other.entity_type == Some(EntityType::Item))
+                            .map(|item| item.id),
+                    );
+                }
+                entities.insert(id, entity);
             }
+        }
 
-            moved_entities
-                .iter()
-                .for_each(|(id, old_location, new_location)| {
-                    grid.move_entity(*id, *old_location, *new_location);
-                })
+        picked_up.sort_unstable();
+        for id in picked_up {
+            self.pick_up(id);
+        }
+
+        let target = self.camera_target();
+        let level_size = self.level_size();
+        self.camera.update(target, level_size);
+
+        if reached_goal {
+            self.complete_level()?;
+        }
+        Ok(())
+    }
+
+    /// Take the item out of the world and tell everyone who is listening for events
+    fn pick_up(&mut self, id: u32) {
+        if let Some(kind) = self.items.get(&id).cloned() {
+            self.remove_entity(id);
+            self.events.push(WorldEvent::ItemPickedUp {
+                entity_id: id,
+                kind: kind.name,
+                value: kind.value,
+            });
         }
     }
 
-    pub fn reset_grid(&mut self, width: f32, height: f32) {
-        let grid = Grid::new(width, height, self.unit_width, self.unit_height);
+    /// The kind of the item entity with this id
+    pub fn item_kind(&self, id: u32) -> Option<&ItemKind> {
+        self.items.get(&id)
+    }
+
+    /// Events that happened since the last time they were drained, oldest first
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, WorldEvent> {
+        self.events.drain(..)
+    }
+
+    pub fn next_level(&mut self) -> Result<()> {
+        self.goto_level(self.current_level_index + 1)
+    }
+
+    /// Rebuild the current level from its level data, such as after the level file changed. The player stays where
+    /// they are when they still fit.
+    pub fn reload_level(&mut self) -> Result<()> {
+        self.change_level(self.current_level_index, PlayerTransition::Keep)
+    }
+
+    /// Replace the entities of the current level with the ones from the level at the index. The player is
+    /// carried over into the new level, either where they were or at the spawn point depending on the player
+    /// transition.
+    pub fn goto_level(&mut self, index: usize) -> Result<()> {
+        self.change_level(index, self.player_transition)
+    }
+
+    fn change_level(&mut self, index: usize, player_transition: PlayerTransition) -> Result<()> {
+        if index >= self.levels.len() {
+            bail!("there is no level at index {}", index);
+        }
+        let from = self.current_level_index;
+        let mut previous = self.take_loaded_level();
+        self.current_level_index = index;
+        if let Err(error) = self.load_level() {
+            // a level that fails to load leaves the world as it was
+            self.restore_loaded_level(previous);
+            return Err(error);
+        }
+        self.all_levels_completed = false;
+
+        let player = previous
+            .player_id
+            .and_then(|id| previous.entities.remove(&id));
+        if let Some(mut player) = player {
+            let keep = player_transition == PlayerTransition::Keep
+                && self.has_room_for(player.bounding_box());
+            if !keep {
+                let spawn_location = match self
+                    .current_level()
+                    .and_then(|level| level.player_spawn_point())
+                {
+                    Some(spawn_point) => Vector2::new(spawn_point.x, spawn_point.y),
+                    None => self.player_start,
+                };
+                player.respawn(spawn_location.x, spawn_location.y);
+            }
+            self.add_entity(player)?;
+        }
+        self.snap_camera();
+        self.events
+            .push(WorldEvent::LevelChanged { from, to: index });
+        Ok(())
+    }
+
+    /// Move everything that loading a level replaces out of the world
+    fn take_loaded_level(&mut self) -> LoadedLevel {
+        LoadedLevel {
+            index: self.current_level_index,
+            all_levels_completed: self.all_levels_completed,
+            grid: self.grid.take(),
+            entities: std::mem::take(&mut self.entities),
+            dynamic_entity_ids: std::mem::take(&mut self.dynamic_entity_ids),
+            items: std::mem::take(&mut self.items),
+            player_id: self.player_id.take(),
+        }
+    }
+
+    fn restore_loaded_level(&mut self, loaded_level: LoadedLevel) {
+        self.current_level_index = loaded_level.index;
+        self.all_levels_completed = loaded_level.all_levels_completed;
+        self.grid = loaded_level.grid;
+        self.entities = loaded_level.entities;
+        self.dynamic_entity_ids = loaded_level.dynamic_entity_ids;
+        self.items = loaded_level.items;
+        self.player_id = loaded_level.player_id;
+    }
+
+    /// True if the middle of the box is inside of the level and the box does not overlap anything collidable
+    fn has_room_for(&self, bounding_box: Rect) -> bool {
+        let (level_width, level_height) = self.level_size();
+        let center_x = bounding_box.x + bounding_box.w / 2.0;
+        let center_y = bounding_box.y + bounding_box.h / 2.0;
+        if !(0.0..=level_width).contains(&center_x) || !(0.0..=level_height).contains(&center_y) {
+            return false;
+        }
+        let grid = match &self.grid {
+            Some(grid) => grid,
+            None => return true,
+        };
+        !grid
+            .query(bounding_box)
+            .into_iter()
+            .filter_map(|id| self.entities.get(id))
+            .filter(|other| other.collidable)
+            .any(|other| other.bounding_box().overlaps(&bounding_box))
+    }
+
+    /// Swap in new level data, such as after the level files changed, and rebuild the current level from it. When
+    /// the current level fails to load the old level data is kept.
+    pub fn replace_levels(&mut self, levels: Vec<Level>) -> Result<()> {
+        if levels.is_empty() {
+            bail!("the world needs at least one level");
+        }
+        let index = self.current_level_index;
+        self.current_level_index = index.min(levels.len() - 1);
+        let previous_levels = std::mem::replace(&mut self.levels, levels);
+        if let Err(error) = self.reload_level() {
+            self.levels = previous_levels;
+            self.current_level_index = index;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Use the values from a config that changed while the game is running. Things that only happen when the
+    /// world is created, such as loading the levels, are left alone. A new unit size rebuilds the current level
+    /// first, if that fails none of the config is used.
+    pub fn apply_config(&mut self, config: &Config) -> Result<()> {
+        if (self.unit_width, self.unit_height)
+            != (config.world_unit_width, config.world_unit_height)
+        {
+            let unit_size = (self.unit_width, self.unit_height);
+            self.set_unit_size(config.world_unit_width, config.world_unit_height);
+            if let Err(error) = self.reload_level() {
+                self.set_unit_size(unit_size.0, unit_size.1);
+                return Err(error);
+            }
+        }
+        self.set_gravity(config.gravity_force);
+        self.camera
+            .set_dead_zone(
+                config.camera_dead_zone_width,
+                config.camera_dead_zone_height,
+            )
+            .set_smoothing(config.camera_smoothing)
+            .set_look_ahead(
+                config.camera_look_ahead_ticks,
+                config.camera_max_look_ahead_x,
+                config.camera_max_look_ahead_y,
+            )
+            .set_shake(Shake::new(
+                config.camera_shake_decay,
+                config.camera_max_shake_x,
+                config.camera_max_shake_y,
+            ));
+        let entities = &mut self.entities;
+        if let Some(player) = self.player_id.and_then(|id| entities.get_mut(&id)) {
+            let old_size = (player.width, player.height);
+            player.set_size(config.player_width, config.player_height);
+            if let Some(physics_system) = &mut player.physics_system {
+                physics_system.apply_config(config);
+            }
+            if let Some(grid) = &mut self.grid {
+                grid.update_entity_bounds(player.location, old_size, player);
+            }
+        }
+        Ok(())
+    }
+
+    fn complete_level(&mut self) -> Result<()> {
+        if self.current_level_index + 1 < self.levels.len() {
+            self.next_level()
+        } else {
+            // only tell once, the player is probably still standing on the goal next tick
+            if !self.all_levels_completed {
+                self.all_levels_completed = true;
+                self.events.push(WorldEvent::AllLevelsCompleted);
+            }
+            Ok(())
+        }
+    }
+
+    /// Replace the grid with an empty one of the given size and index the entities that the world owns into it
+    pub fn reset_grid(&mut self, width: f32, height: f32) -> Result<()> {
+        let mut grid = Grid::new(width, height, self.unit_width, self.unit_height);
+        self.entities
+            .values()
+            .try_for_each(|entity| grid.insert(entity))?;
         self.grid = Some(grid);
+        Ok(())
     }
 
     /// Load a new level into the world.
     /// - reset the grid
     /// - create platforms and put them into the grid
-    /// - load items and put into the grid
-    /// - load enemies and put into the grid
+    /// - load items and put into the grid, every item needs a kind that the level defines
+    /// - load enemies with the behavior the level gives them and put into the grid
     /// - load player and put into grid
-    pub fn load_level(&mut self, entity_builder: &mut EntityBuilder) {
-        let level = self.levels[self.current_level_index].clone();
-        self.reset_grid(level.width, level.height);
+    pub fn load_level(&mut self) -> Result<()> {
+        let level = self
+            .levels
+            .get(self.current_level_index)
+            .ok_or_else(|| eyre!("there is no level at index {}", self.current_level_index))?
+            .clone();
+        self.entities.clear();
+        self.dynamic_entity_ids.clear();
+        self.items.clear();
+        self.player_id = None;
+        self.reset_grid(level.width, level.height)?;
 
         level
             .entity_datas
             .iter()
-            .for_each(|entity_data| match entity_data.entity_type {
-                entity_type::EntityType::Player => {}
-                entity_type::EntityType::Platform => {
-                    let platform = entity_builder
+            .try_for_each(|entity_data| match entity_data.entity_type {
+                entity_type::EntityType::Player => Ok(()),
+                entity_type::EntityType::Platform | entity_type::EntityType::Goal => {
+                    let draw_system =
+                        PlatformDrawSystem::new(entity_data.color, entity_data.sprite.clone());
+                    let builder = self
+                        .entity_builder
+                        .create_entity()
+                        .location(entity_data.x, entity_data.y)
+                        .size(entity_data.width, entity_data.height)
+                        .entity_type(entity_data.entity_type)
+                        .layer(entity_data.layer())
+                        .z(entity_data.z)
+                        .draw_system(Box::new(draw_system));
+                    // the player walks through goals
+                    if entity_data.entity_type == EntityType::Platform {
+                        builder.collidable();
+                    }
+                    let entity = builder.build();
+                    self.add_entity(entity)
+                }
+                entity_type::EntityType::Item => {
+                    let item_kind = entity_data
+                        .item_kind
+                        .as_deref()
+                        .and_then(|name| level.get_item_kind(name))
+                        .ok_or_else(|| {
+                            eyre!(
+                                "the item at ({}, {}) needs an item kind from the level, it has {:?}",
+                                entity_data.x,
+                                entity_data.y,
+                                entity_data.item_kind
+                            )
+                        })?;
+                    let sprite = entity_data
+                        .sprite
+                        .clone()
+                        .or_else(|| item_kind.sprite.clone());
+                    let entity = self
+                        .entity_builder
                         .create_entity()
-                        .collidable()
                         .location(entity_data.x, entity_data.y)
                         .size(entity_data.width, entity_data.height)
+                        .entity_type(EntityType::Item)
+                        .layer(entity_data.layer())
+                        .z(entity_data.z)
+                        .draw_system(Box::new(PlatformDrawSystem::new(entity_data.color, sprite)))
                         .build();
-                    self.add_entity(platform);
+                    self.items.insert(entity.id, item_kind.clone());
+                    self.add_entity(entity)
                 }
-            });
+                entity_type::EntityType::Enemy => {
+                    let flying = entity_data
+                        .behavior
+                        .as_ref()
+                        .is_some_and(|behavior| behavior.is_flying());
+                    let builder = self
+                        .entity_builder
+                        .create_entity()
+                        .location(entity_data.x, entity_data.y)
+                        .size(entity_data.width, entity_data.height)
+                        .entity_type(EntityType::Enemy)
+                        .layer(entity_data.layer())
+                        .z(entity_data.z)
+                        .draw_system(Box::new(PlatformDrawSystem::new(
+                            entity_data.color,
+                            entity_data.sprite.clone(),
+                        )))
+                        .physics_system(Box::new(EnemyPhysicsSystem::new(flying)));
+                    if !flying {
+                        builder.affected_by_gravity();
+                    }
+                    if let Some(behavior) = &entity_data.behavior {
+                        builder.ai_system(behavior.ai_system());
+                    }
+                    let entity = builder.build();
+                    self.add_entity(entity)
+                }
+            })
+    }
+
+    pub fn current_level(&self) -> Option<&Level> {
+        self.levels.get(self.current_level_index)
+    }
+
+    pub fn levels(&self) -> &[Level] {
+        &self.levels
     }
 
     pub fn add_level(&mut self, level: Level) -> &mut Self {
@@ -145,6 +757,82 @@ impl World {
         self.camera = camera;
         self
     }
+
+    /// Have the camera follow the entity, starting out centered on it
+    pub fn camera_follow(&mut self, id: u32) -> &mut Self {
+        self.camera.follow(id);
+        self.snap_camera();
+        self
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// For shaking and zooming the camera
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    fn camera_target(&self) -> Option<CameraTarget> {
+        let entity = self.entities.get(&self.camera.target_id()?)?;
+        let velocity = entity
+            .physics_system
+            .as_ref()
+            .map(|physics_system| *physics_system.get_velocity())
+            .unwrap_or_else(|| Vector2::new(0.0, 0.0));
+        Some(CameraTarget {
+            location: entity.location,
+            velocity,
+        })
+    }
+
+    fn snap_camera(&mut self) {
+        if let Some(target) = self.camera_target() {
+            let level_size = self.level_size();
+            self.camera.snap_to(target.location, level_size);
+        }
+    }
+
+    /// The size of the current level, or of the world when no level has been loaded
+    fn level_size(&self) -> (f32, f32) {
+        self.current_level()
+            .map(|level| (level.width, level.height))
+            .unwrap_or((self.width, self.height))
+    }
+}
+
+/// Part of the tile layer to draw, either the tile batches with a z or an entity that is not batched
+enum TileLayerPart<'a> {
+    Tiles(i32),
+    Entity(&'a Entity),
+}
+
+/// Put the tile batches between the entities of the tile layer by z, both sorted back to front. The tiles go under
+/// the entities with the same z.
+fn tile_layer_parts<'a>(tile_zs: &[i32], entities: &[&'a Entity]) -> Vec<TileLayerPart<'a>> {
+    let mut parts = vec![];
+    let mut tile_zs = tile_zs.iter().peekable();
+    for entity in entities {
+        while let Some(z) = tile_zs.next_if(|z| **z <= entity.z) {
+            parts.push(TileLayerPart::Tiles(*z));
+        }
+        parts.push(TileLayerPart::Entity(entity));
+    }
+    parts.extend(tile_zs.map(|z| TileLayerPart::Tiles(*z)));
+    parts
+}
+
+/// Everything that loading a level replaces, kept while changing levels so that a level that fails to load can be
+/// undone
+struct LoadedLevel {
+    index: usize,
+    all_levels_completed: bool,
+    grid: Option<Grid>,
+    entities: BTreeMap<u32, Entity>,
+    dynamic_entity_ids: BTreeSet<u32>,
+    items: BTreeMap<u32, ItemKind>,
+    player_id: Option<u32>,
 }
 
 impl Default for World {
@@ -165,15 +853,41 @@ impl Default for World {
             levels: vec![],
             current_level_index: 0,
             camera: Camera::default(),
-            entities: HashMap::new(),
+            entities: BTreeMap::new(),
+            dynamic_entity_ids: BTreeSet::new(),
+            entity_builder: EntityBuilder::new(),
+            player_id: None,
+            player_start: Vector2::new(0.0, 0.0),
+            player_transition: PlayerTransition::default(),
+            events: vec![],
+            all_levels_completed: false,
+            items: BTreeMap::new(),
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use ggez::graphics::Rect;
+
+    use crate::entity::entity_data::EntityData;
+    use crate::entity::entity_state::EntityState;
+    use crate::input::action::Action;
+    use crate::level::spawn_point::SpawnPoint;
+
     use super::*;
 
+    fn create_built_world() -> World {
+        let mut world = World::default();
+        world
+            .set_gravity(1.0)
+            .set_unit_size(10.0, 10.0)
+            .add_level(Level::new(100.0, 100.0, vec![]))
+            .build()
+            .unwrap();
+        world
+    }
+
     #[test]
     #[allow(clippy::float_cmp)]
     fn ci_test_add_gravity_to_the_world() {
@@ -227,4 +941,574 @@ mod test {
         let _updated_world: &mut World = world.set_camera(camera);
         assert_eq!(world.camera, camera);
     }
+
+    #[test]
+    fn ci_test_adding_an_entity_before_building_fails() {
+        let mut world = World::default();
+        let entity = world.entity_builder().create_entity().build();
+        assert!(world.add_entity(entity).is_err());
+        assert!(world.entities.is_empty());
+    }
+
+    #[test]
+    fn ci_test_add_and_remove_entity() {
+        let mut world = create_built_world();
+        let entity = world
+            .entity_builder()
+            .create_entity()
+            .location(15.0, 15.0)
+            .build();
+        let id = entity.id;
+        world.add_entity(entity).unwrap();
+        assert_eq!(world.get_entity(id).unwrap().id, id);
+
+        let removed = world.remove_entity(id).unwrap();
+        assert_eq!(removed.id, id);
+        assert!(world.get_entity(id).is_none());
+        let grid = world.grid.as_ref().unwrap();
+        assert!(grid.query(Rect::new(0.0, 0.0, 100.0, 100.0)).is_empty());
+    }
+
+    #[test]
+    fn ci_test_load_level_creates_platforms_owned_by_the_world() {
+        let mut world = World::default();
+        let platform = EntityData {
+            x: 25.0,
+            y: 25.0,
+            width: 10.0,
+            height: 10.0,
+            ..EntityData::default()
+        };
+        world
+            .set_unit_size(10.0, 10.0)
+            .add_level(Level::new(100.0, 100.0, vec![platform]))
+            .build()
+            .unwrap();
+        assert_eq!(world.entities.len(), 1);
+        let grid = world.grid.as_ref().unwrap();
+        let found = grid.query(Rect::new(20.0, 20.0, 1.0, 1.0));
+        assert_eq!(found.len(), 1);
+        assert!(world.get_entity(*found[0]).unwrap().collidable);
+    }
+
+    #[test]
+    fn ci_test_update_moves_entities_through_the_grid() {
+        let mut world = create_built_world();
+        let entity = world
+            .entity_builder()
+            .create_entity()
+            .location(15.0, 5.0)
+            .affected_by_gravity()
+            .physics_system(Box::new(PlayerPhysicsSystem::default()))
+            .build();
+        let id = entity.id;
+        world.add_entity(entity).unwrap();
+
+        for _ in 0..5 {
+            world.update(&InputState::new()).unwrap();
+        }
+
+        let location = world.get_entity(id).unwrap().location;
+        assert!(location.y > 5.0);
+        let grid = world.grid.as_ref().unwrap();
+        assert!(grid.query(Rect::new(15.0, 5.0, 0.0, 0.0)).is_empty());
+        assert_eq!(
+            grid.query(Rect::new(location.x, location.y, 0.0, 0.0)),
+            vec![&id]
+        );
+    }
+
+    /// Two levels where the player starts on top of the goal of the first one
+    fn create_world_with_levels(player_transition: PlayerTransition) -> (World, u32) {
+        let goal = EntityData {
+            x: 50.0,
+            y: 50.0,
+            width: 10.0,
+            height: 10.0,
+            entity_type: EntityType::Goal,
+            ..EntityData::default()
+        };
+        let mut first_level = Level::new(100.0, 100.0, vec![goal]);
+        first_level.add_spawn_point(SpawnPoint::new("player", 50.0, 50.0));
+        let mut second_level = Level::new(200.0, 200.0, vec![]);
+        second_level.add_spawn_point(SpawnPoint::new("player", 150.0, 20.0));
+
+        let mut world = World::default();
+        world
+            .set_gravity(1.0)
+            .set_unit_size(10.0, 10.0)
+            .set_player_transition(player_transition)
+            .add_level(first_level)
+            .add_level(second_level)
+            .build()
+            .unwrap();
+        let player = world
+            .entity_builder()
+            .create_entity()
+            .location(50.0, 50.0)
+            .size(10.0, 10.0)
+            .affected_by_gravity()
+            .physics_system(Box::new(PlayerPhysicsSystem::default()))
+            .player_controlled()
+            .build();
+        let player_id = player.id;
+        world.add_entity(player).unwrap();
+        (world, player_id)
+    }
+
+    fn item(x: f32, y: f32, kind: &str) -> EntityData {
+        EntityData {
+            x,
+            y,
+            width: 10.0,
+            height: 10.0,
+            entity_type: EntityType::Item,
+            item_kind: Some(kind.to_owned()),
+            ..EntityData::default()
+        }
+    }
+
+    #[test]
+    fn ci_test_touching_an_item_picks_it_up() {
+        let mut level = Level::new(
+            100.0,
+            100.0,
+            vec![item(50.0, 55.0, "coin"), item(90.0, 90.0, "coin")],
+        );
+        level.add_spawn_point(SpawnPoint::new("player", 50.0, 50.0));
+        level.item_kinds.push(ItemKind::new("coin", 10));
+        let mut world =
+            World::from_levels(&Config::default(), vec![level], (100.0, 100.0)).unwrap();
+        let coins: Vec<u32> = world
+            .entities()
+            .into_iter()
+            .filter(|entity| entity.entity_type == Some(EntityType::Item))
+            .map(|entity| entity.id)
+            .collect();
+        assert_eq!(world.item_kind(coins[0]), Some(&ItemKind::new("coin", 10)));
+
+        world.update(&InputState::new()).unwrap();
+
+        assert_eq!(
+            world.drain_events().collect::<Vec<WorldEvent>>(),
+            vec![WorldEvent::ItemPickedUp {
+                entity_id: coins[0],
+                kind: "coin".to_owned(),
+                value: 10
+            }]
+        );
+        assert!(world.get_entity(coins[0]).is_none());
+        assert!(world.item_kind(coins[0]).is_none());
+        let grid = world.grid.as_ref().unwrap();
+        assert!(!grid
+            .query(Rect::new(45.0, 50.0, 10.0, 10.0))
+            .contains(&&coins[0]));
+        assert!(world.get_entity(coins[1]).is_some());
+        world.update(&InputState::new()).unwrap();
+        assert_eq!(world.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn ci_test_items_need_a_kind_from_the_level() {
+        let level = Level::new(100.0, 100.0, vec![item(50.0, 55.0, "gem")]);
+        let Err(error) = World::from_levels(&Config::default(), vec![level], (100.0, 100.0)) else {
+            panic!("the level loaded with an item of an unknown kind");
+        };
+        assert_eq!(
+            error.to_string(),
+            r#"the item at (50, 55) needs an item kind from the level, it has Some("gem")"#
+        );
+    }
+
+    #[test]
+    fn ci_test_enemies_move_by_their_behavior_in_the_fixed_update() {
+        let level: Level = r#"(
+            width: 200.0,
+            height: 200.0,
+            spawn_points: [(name: "player", x: 190.0, y: 10.0)],
+            entities: [
+                (x: 50.0, y: 105.0, width: 100.0, height: 10.0, entity_type: Platform),
+                (x: 50.0, y: 95.0, width: 10.0, height: 10.0, entity_type: Enemy, behavior: Some(WalkToLedge(speed: 2.0))),
+                (x: 150.0, y: 50.0, width: 10.0, height: 10.0, entity_type: Enemy, behavior: Some(SineFlight(speed: 0.0, amplitude: 20.0, period_ticks: 40))),
+            ],
+        )"#
+        .parse()
+        .unwrap();
+        let config = Config {
+            gravity_force: 1.0,
+            ..Config::default()
+        };
+        let mut world = World::from_levels(&config, vec![level], (200.0, 200.0)).unwrap();
+        let enemies: Vec<u32> = world
+            .entities()
+            .into_iter()
+            .filter(|entity| entity.entity_type == Some(EntityType::Enemy))
+            .map(|entity| entity.id)
+            .collect();
+
+        let mut input = InputState::new();
+        let mut walker_xs = vec![];
+        let mut flyer_highest: f32 = 50.0;
+        for _ in 0..100 {
+            world.step(1, &mut input).unwrap();
+            walker_xs.push(world.get_entity(enemies[0]).unwrap().location.x);
+            flyer_highest = flyer_highest.min(world.get_entity(enemies[1]).unwrap().location.y);
+        }
+
+        let walker = world.get_entity(enemies[0]).unwrap();
+        assert_eq!(walker.get_state(), EntityState::Standing);
+        assert_eq!(walker.location.y, 95.0);
+        // it turns around at both ends of the platform instead of walking off
+        assert!(walker_xs.iter().all(|x| (0.0..=100.0).contains(x)));
+        assert!(walker_xs.iter().cloned().fold(f32::MAX, f32::min) < 10.0);
+        assert!(walker_xs.iter().cloned().fold(f32::MIN, f32::max) > 90.0);
+        // the flyer ignores gravity and bobs around where it started
+        let flyer = world.get_entity(enemies[1]).unwrap();
+        assert!((flyer.location.y - 50.0).abs() < 0.01);
+        assert!((flyer_highest - 30.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn ci_test_step_runs_ticks_without_a_context() {
+        let mut world = World::from_config(&Config::default(), (1280.0, 720.0)).unwrap();
+        let start = world.player().unwrap().location;
+        let mut input = InputState::new();
+        input.press(Action::MoveRight);
+        world.step(50, &mut input).unwrap();
+
+        let player = world.player().unwrap();
+        assert!(player.location.x > start.x);
+        assert!(player.location.y > start.y);
+        assert_eq!(player.get_state(), EntityState::Falling);
+        assert!(!input.was_pressed(Action::MoveRight));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_entities_are_drawn_between_the_last_two_updates() {
+        let (mut world, _) = create_world_with_levels(PlayerTransition::Respawn);
+        world.goto_level(1).unwrap();
+        // respawning is a jump, so there is nothing to draw between
+        let player = world.player().unwrap();
+        assert_eq!(player.interpolated_location(0.5), Vector2::new(150.0, 20.0));
+
+        world.update(&InputState::new()).unwrap();
+        world.update(&InputState::new()).unwrap();
+        let player = world.player().unwrap();
+        assert_eq!(player.interpolated_location(0.0), Vector2::new(150.0, 21.0));
+        assert_eq!(
+            player.interpolated_location(0.25),
+            Vector2::new(150.0, 21.5)
+        );
+        assert_eq!(player.interpolated_location(0.5), Vector2::new(150.0, 22.0));
+        assert_eq!(player.interpolated_location(1.0), player.location);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_goto_level_respawns_the_player() {
+        let (mut world, player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        let mut player = world.remove_entity(player_id).unwrap();
+        let physics_system = player.physics_system.as_mut().unwrap();
+        physics_system.apply_force(&Vector2::new(0.0, 5.0));
+        player.update(&Vector2::new(0.0, 0.0), &[], &InputState::new());
+        world.add_entity(player).unwrap();
+
+        world.goto_level(1).unwrap();
+
+        assert_eq!(
+            world.drain_events().collect::<Vec<WorldEvent>>(),
+            vec![WorldEvent::LevelChanged { from: 0, to: 1 }]
+        );
+        assert_eq!(world.drain_events().count(), 0);
+        assert_eq!(world.current_level().unwrap().width, 200.0);
+        let player = world.player().unwrap();
+        assert_eq!(player.id, player_id);
+        assert_eq!(player.location, Vector2::new(150.0, 20.0));
+        assert_eq!(
+            player.physics_system.as_ref().unwrap().get_velocity(),
+            &Vector2::new(0.0, 0.0)
+        );
+        let grid = world.grid.as_ref().unwrap();
+        assert_eq!(grid.query(player.bounding_box()), vec![&player_id]);
+    }
+
+    #[test]
+    fn ci_test_goto_level_can_keep_the_player_where_they_are() {
+        let (mut world, _player_id) = create_world_with_levels(PlayerTransition::Keep);
+        world.next_level().unwrap();
+        assert_eq!(world.player().unwrap().location, Vector2::new(50.0, 50.0));
+    }
+
+    #[test]
+    fn ci_test_respawning_without_a_spawn_point_uses_the_config_start() {
+        let mut second_level = Level::new(200.0, 200.0, vec![]);
+        second_level.add_spawn_point(SpawnPoint::new("player", 150.0, 20.0));
+        let levels = vec![
+            Level::new(200.0, 200.0, vec![]),
+            second_level,
+            Level::new(200.0, 200.0, vec![]),
+        ];
+        let config = Config::default();
+        let mut world = World::from_levels(&config, levels, (200.0, 200.0)).unwrap();
+        let mut player = world.remove_entity(world.player_id.unwrap()).unwrap();
+        player.set_location(120.0, 80.0);
+        world.add_entity(player).unwrap();
+
+        world.set_player_transition(PlayerTransition::Keep);
+        world.goto_level(1).unwrap();
+        assert_eq!(world.player().unwrap().location, Vector2::new(120.0, 80.0));
+        world.set_player_transition(PlayerTransition::Respawn);
+        world.goto_level(2).unwrap();
+        assert_eq!(
+            world.player().unwrap().location,
+            Vector2::new(config.player_start_x, config.player_start_y)
+        );
+    }
+
+    #[test]
+    fn ci_test_goto_level_that_does_not_exist_fails() {
+        let (mut world, player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        assert!(world.goto_level(2).is_err());
+        assert_eq!(world.current_level_index, 0);
+        assert_eq!(world.player().unwrap().id, player_id);
+        assert_eq!(world.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn ci_test_reload_level_recreates_the_level_entities() {
+        let (mut world, player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        world.reload_level().unwrap();
+        assert_eq!(world.entities.len(), 2);
+        assert_eq!(world.player().unwrap().id, player_id);
+        assert_eq!(
+            world.drain_events().collect::<Vec<WorldEvent>>(),
+            vec![WorldEvent::LevelChanged { from: 0, to: 0 }]
+        );
+    }
+
+    #[test]
+    fn ci_test_reloading_keeps_the_player_where_they_are_when_they_fit() {
+        let (mut world, player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        let mut player = world.remove_entity(player_id).unwrap();
+        player.set_location(20.0, 80.0);
+        world.add_entity(player).unwrap();
+
+        world.reload_level().unwrap();
+        assert_eq!(world.player().unwrap().location, Vector2::new(20.0, 80.0));
+
+        // a platform now where the player is standing pushes them back to the spawn point
+        let mut levels = world.levels().to_vec();
+        levels[0].entity_datas.push(EntityData {
+            x: 20.0,
+            y: 80.0,
+            width: 10.0,
+            height: 10.0,
+            ..EntityData::default()
+        });
+        world.replace_levels(levels).unwrap();
+        assert_eq!(world.player().unwrap().location, Vector2::new(50.0, 50.0));
+    }
+
+    #[test]
+    fn ci_test_a_level_that_fails_to_load_leaves_the_world_as_it_was() {
+        let (mut world, player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        let entity_ids: Vec<u32> = world.entities().iter().map(|entity| entity.id).collect();
+        let mut levels = world.levels().to_vec();
+        levels[0].entity_datas.push(item(20.0, 20.0, "gem"));
+
+        assert!(world.replace_levels(levels).is_err());
+        assert_eq!(world.player().unwrap().id, player_id);
+        assert_eq!(
+            world
+                .entities()
+                .iter()
+                .map(|entity| entity.id)
+                .collect::<Vec<u32>>(),
+            entity_ids
+        );
+        assert_eq!(world.levels()[0].entity_datas.len(), 1);
+        let grid = world.grid.as_ref().unwrap();
+        assert!(grid
+            .query(Rect::new(50.0, 50.0, 0.0, 0.0))
+            .contains(&&player_id));
+        assert_eq!(world.drain_events().count(), 0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn ci_test_apply_config_while_running() {
+        let (mut world, player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        world.set_player_start(5.0, 5.0);
+        let config = Config {
+            gravity_force: 0.5,
+            player_width: 30.0,
+            player_height: 30.0,
+            world_unit_width: 10.0,
+            world_unit_height: 10.0,
+            ..Config::default()
+        };
+        world.apply_config(&config).unwrap();
+        assert_eq!(world.gravity, Vector2::new(0.0, 0.5));
+        let player = world.player().unwrap();
+        assert_eq!(player.id, player_id);
+        assert_eq!((player.width, player.height), (30.0, 30.0));
+        assert_eq!(player.location, Vector2::new(50.0, 50.0));
+        assert_eq!(world.player_start, Vector2::new(5.0, 5.0));
+        // the bigger player reaches into cells that the old size did not
+        let grid = world.grid.as_ref().unwrap();
+        assert!(grid
+            .query(Rect::new(62.0, 62.0, 0.0, 0.0))
+            .contains(&&player_id));
+    }
+
+    #[test]
+    fn ci_test_touching_the_goal_moves_to_the_next_level() {
+        let (mut world, _player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        world.update(&InputState::new()).unwrap();
+        assert_eq!(world.current_level_index, 1);
+        assert_eq!(
+            world.drain_events().collect::<Vec<WorldEvent>>(),
+            vec![WorldEvent::LevelChanged { from: 0, to: 1 }]
+        );
+    }
+
+    #[test]
+    fn ci_test_touching_the_last_goal_completes_all_levels_once() {
+        let (mut world, _player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        world.levels.truncate(1);
+        world.update(&InputState::new()).unwrap();
+        world.update(&InputState::new()).unwrap();
+        assert_eq!(world.current_level_index, 0);
+        assert_eq!(
+            world.drain_events().collect::<Vec<WorldEvent>>(),
+            vec![WorldEvent::AllLevelsCompleted]
+        );
+    }
+
+    #[test]
+    fn ci_test_camera_follows_the_player_into_the_next_level() {
+        let (mut world, player_id) = create_world_with_levels(PlayerTransition::Respawn);
+        world
+            .set_camera(Camera::new(0.0, 0.0, 40.0, 40.0))
+            .camera_follow(player_id);
+        assert_eq!(world.camera.view(0.0), Rect::new(30.0, 30.0, 40.0, 40.0));
+
+        world.update(&InputState::new()).unwrap();
+        assert_eq!(world.current_level_index, 1);
+        assert_eq!(world.camera.view(0.5), Rect::new(130.0, 0.0, 40.0, 40.0));
+    }
+
+    #[test]
+    fn ci_test_animation_events_become_world_events() {
+        let animation_set = AnimationSet::from_ron_str(
+            r#"(
+                clips: {"blink": (mode: Once, frames: [(sprite: "eye", ticks: 5, events: ["blink"])])},
+                states: [(Falling, "blink")],
+            )"#,
+        )
+        .unwrap();
+        let mut world = create_built_world();
+        let entity = world
+            .entity_builder()
+            .create_entity()
+            .location(50.0, 10.0)
+            .affected_by_gravity()
+            .physics_system(Box::new(PlayerPhysicsSystem::default()))
+            .draw_system(Box::new(
+                PlayerDrawSystem::default().with_animator(Animator::new(animation_set)),
+            ))
+            .build();
+        let id = entity.id;
+        world.add_entity(entity).unwrap();
+
+        world.update(&InputState::new()).unwrap();
+        world.update(&InputState::new()).unwrap();
+        assert_eq!(
+            world.drain_events().collect::<Vec<WorldEvent>>(),
+            vec![WorldEvent::Animation {
+                entity_id: id,
+                name: "blink".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn ci_test_tiles_are_drawn_between_the_tile_layer_entities_by_z() {
+        let mut back = Entity::new();
+        back.z = -1;
+        let mut middle = Entity::new();
+        middle.z = 2;
+        let mut front = Entity::new();
+        front.z = 5;
+        let parts = tile_layer_parts(&[0, 2, 7], &[&back, &middle, &front]);
+        let order: Vec<String> = parts
+            .iter()
+            .map(|part| match part {
+                TileLayerPart::Tiles(z) => format!("tiles {}", z),
+                TileLayerPart::Entity(entity) => format!("entity {}", entity.z),
+            })
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                "entity -1",
+                "tiles 0",
+                "tiles 2",
+                "entity 2",
+                "entity 5",
+                "tiles 7"
+            ]
+        );
+    }
+
+    #[test]
+    fn ci_test_level_appearance_reaches_the_entities() {
+        let grass = EntityData {
+            x: 25.0,
+            y: 25.0,
+            width: 10.0,
+            height: 10.0,
+            sprite: Some("grass".to_owned()),
+            z: 2,
+            ..EntityData::default()
+        };
+        let plain = EntityData {
+            x: 45.0,
+            y: 25.0,
+            width: 10.0,
+            height: 10.0,
+            ..EntityData::default()
+        };
+        // only the tile layer is batched, so the leaves can be drawn in front of the player
+        let leaves = EntityData {
+            x: 65.0,
+            y: 25.0,
+            width: 10.0,
+            height: 10.0,
+            sprite: Some("leaves".to_owned()),
+            layer: Some(RenderLayer::Foreground),
+            ..EntityData::default()
+        };
+        let mut world = World::default();
+        world
+            .set_unit_size(10.0, 10.0)
+            .add_level(Level::new(100.0, 100.0, vec![grass, plain, leaves]))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            world.batched_sprites(),
+            vec![("grass".to_owned(), Rect::new(20.0, 20.0, 10.0, 10.0), 2)]
+        );
+        let mut z_values: Vec<i32> = world.entities.values().map(|entity| entity.z).collect();
+        z_values.sort_unstable();
+        assert_eq!(z_values, vec![0, 0, 2]);
+        let foreground_count = world
+            .entities
+            .values()
+            .filter(|entity| entity.layer == RenderLayer::Foreground)
+            .count();
+        assert_eq!(foreground_count, 1);
+    }
 }
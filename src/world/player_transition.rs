@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens to the player when the world moves to another level
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum PlayerTransition {
+    /// Keep the location and speed the player had in the previous level, unless that is outside of the new level
+    /// or inside of one of its platforms
+    Keep,
+    /// Move the player to the spawn point of the new level and stop them
+    #[default]
+    Respawn,
+}
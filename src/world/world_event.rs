@@ -0,0 +1,21 @@
+/// Something that happened in the world during an update that the game might want to react to
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorldEvent {
+    LevelChanged {
+        from: usize,
+        to: usize,
+    },
+    /// The player reached the goal of the last level
+    AllLevelsCompleted,
+    /// The player touched an item, which has been taken out of the world
+    ItemPickedUp {
+        entity_id: u32,
+        kind: String,
+        value: i32,
+    },
+    /// A frame of an entity's animation started that has an event, such as a footstep
+    Animation {
+        entity_id: u32,
+        name: String,
+    },
+}